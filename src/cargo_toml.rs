@@ -21,7 +21,6 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
-use std::mem::take;
 use std::path::{Path, PathBuf};
 use std::{fs, io};
 pub use toml::Value;
@@ -45,21 +44,61 @@ pub type LintSet = BTreeMap<String, Lint>;
 pub type LintGroups = BTreeMap<String, LintSet>;
 
 mod afs;
+mod deprecated;
+mod discovery;
+#[cfg(feature = "edit")]
+mod edit;
+mod edition;
 mod error;
+mod explicit;
+mod hoist;
 mod inheritable;
+mod products;
+mod provenance;
+mod recover;
+mod release;
+mod script;
+mod strict;
+mod workspace;
+mod workspace_lint;
 pub use crate::afs::*;
+pub use crate::deprecated::*;
+pub use crate::discovery::*;
+pub use crate::edition::*;
 pub use crate::error::Error;
+pub use crate::explicit::*;
+pub use crate::hoist::*;
 pub use crate::inheritable::Inheritable;
+pub use crate::products::*;
+pub use crate::provenance::*;
+pub use crate::recover::*;
+pub use crate::release::*;
+pub use crate::script::*;
+pub use crate::strict::*;
+pub use crate::workspace::*;
+pub use crate::workspace_lint::*;
 
 #[cfg(feature = "features")]
 #[cfg_attr(docsrs, doc(cfg(feature = "features")))]
 pub mod features;
 
+#[cfg(feature = "index")]
+#[cfg_attr(docsrs, doc(cfg(feature = "index")))]
+pub mod index;
+
+#[cfg(feature = "schemars")]
+mod schema;
+#[cfg(feature = "schemars")]
+#[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
+pub use crate::schema::manifest_json_schema;
+
 /// The top-level `Cargo.toml` structure. **This is the main type in this library.**
 ///
 /// The `Metadata` is a generic type for `[package.metadata]` table. You can replace it with
 /// your own struct type if you use the metadata and don't want to use the catch-all `Value` type.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "Manifest", bound = ""))]
 #[serde(rename_all = "kebab-case")]
 pub struct Manifest<Metadata = Value> {
     /// Package definition (a cargo crate)
@@ -136,10 +175,25 @@ pub struct Manifest<Metadata = Value> {
     /// Lints
     #[serde(default, skip_serializing_if = "Inheritable::<LintGroups>::is_empty")]
     pub lints: Inheritable<LintGroups>,
+
+    /// Values that have been inherited from the workspace. It's not a part of `Cargo.toml`.
+    #[serde(skip)]
+    pub provenance: Provenance,
+
+    /// Keys that this crate doesn't support, kept to be serialized back
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
+    pub extra: BTreeMap<String, Value>,
+
+    /// Which keys of this table have been written in `Cargo.toml`, as opposed to filled in with defaults
+    #[serde(skip)]
+    pub explicit: ExplicitKeys,
 }
 
 /// A manifest can contain both a package and workspace-wide properties
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "Workspace", bound = ""))]
 #[serde(rename_all = "kebab-case")]
 pub struct Workspace<Metadata = Value> {
     /// Relative paths of crates in here
@@ -162,6 +216,7 @@ pub struct Workspace<Metadata = Value> {
 
     /// Shared info
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<crate::schema::AnyValue>"))]
     pub metadata: Option<Metadata>,
 
     /// Compatibility setting
@@ -175,10 +230,20 @@ pub struct Workspace<Metadata = Value> {
     /// Workspace-level lint groups
     #[serde(default, skip_serializing_if = "LintGroups::is_empty")]
     pub lints: LintGroups,
+
+    /// Keys that this crate doesn't support, kept to be serialized back
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
+    pub extra: BTreeMap<String, Value>,
+
+    /// Which keys of this table have been written in `Cargo.toml`, as opposed to filled in with defaults
+    #[serde(skip)]
+    pub explicit: ExplicitKeys,
 }
 
 /// Workspace can predefine properties that can be inherited via `{ workspace = true }` in its member packages.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct PackageTemplate {
@@ -307,19 +372,27 @@ impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
 
     #[inline(never)]
     fn from_slice_with_metadata_str(cargo_toml_content: &str) -> Result<Self, Error> {
+        // deserializing from the source keeps line numbers in errors
         let mut manifest: Self = toml::from_str(cargo_toml_content)?;
+        let raw: toml::Table = toml::from_str(cargo_toml_content)?;
+        manifest.set_explicit_keys(&raw);
+        manifest.set_publish_default();
+        Ok(manifest)
+    }
 
-        if let Some(package) = &mut manifest.package {
-            // This is a clumsy implementation of Cargo's rule that missing version defaults publish to false.
-            // Serde just doesn't support such relationship for default field values, so this will be incorrect
-            // for explicit `version = "0.0.0"` and `publish = true`.
-            if package.version.get().is_ok_and(|v| v == "0.0.0") && package.publish.get().is_ok_and(|p| p.is_default()) {
-                package.publish = Inheritable::Set(Publish::Flag(false));
-            }
-        }
+    pub(crate) fn from_table(raw: toml::Table) -> Result<Self, Error> {
+        let mut manifest = Self::deserialize(Value::Table(raw.clone()))?;
+        manifest.set_explicit_keys(&raw);
+        manifest.set_publish_default();
         Ok(manifest)
     }
 
+    pub(crate) fn set_publish_default(&mut self) {
+        if let Some(package) = &mut self.package {
+            package.set_publish_default();
+        }
+    }
+
     /// Parse contents from `Cargo.toml` file on disk, with custom Serde-compatible metadata type.
     ///
     /// Calls [`Manifest::complete_from_path`], so it will load a workspace if necessary.
@@ -399,8 +472,8 @@ impl<Metadata> Manifest<Metadata> {
 
         match res.and_then(|()| self.complete_from_abstract_filesystem_inner(&fs)) {
             Ok(()) => Ok(()),
-            Err(e @ Error::Workspace(_)) => return Err(e),
-            Err(e) => return Err(Error::Workspace(Box::new((e.into(), err_path.map(PathBuf::from))))),
+            Err(e @ Error::Workspace(_)) => Err(e),
+            Err(e) => Err(Error::Workspace(Box::new((e, err_path.map(PathBuf::from))))),
         }
     }
 
@@ -425,72 +498,90 @@ impl<Metadata> Manifest<Metadata> {
             workspace_base_path
         };
 
-        inherit_dependencies(&mut self.dependencies, workspace, workspace_base_path)?;
-        inherit_dependencies(&mut self.build_dependencies, workspace, workspace_base_path)?;
-        inherit_dependencies(&mut self.dev_dependencies, workspace, workspace_base_path)?;
+        let mut provenance = Provenance::default();
+        inherit_dependencies(&mut self.dependencies, workspace, workspace_base_path, &mut |k, o, d| provenance.add_dependency(Kind::Normal, None, k, o, d))?;
+        inherit_dependencies(&mut self.build_dependencies, workspace, workspace_base_path, &mut |k, o, d| provenance.add_dependency(Kind::Build, None, k, o, d))?;
+        inherit_dependencies(&mut self.dev_dependencies, workspace, workspace_base_path, &mut |k, o, d| provenance.add_dependency(Kind::Dev, None, k, o, d))?;
 
-        for target in self.target.values_mut() {
-            inherit_dependencies(&mut target.dependencies, workspace, workspace_base_path)?;
-            inherit_dependencies(&mut target.build_dependencies, workspace, workspace_base_path)?;
-            inherit_dependencies(&mut target.dev_dependencies, workspace, workspace_base_path)?;
+        for (cfg, target) in &mut self.target {
+            inherit_dependencies(&mut target.dependencies, workspace, workspace_base_path, &mut |k, o, d| provenance.add_dependency(Kind::Normal, Some(cfg), k, o, d))?;
+            inherit_dependencies(&mut target.build_dependencies, workspace, workspace_base_path, &mut |k, o, d| provenance.add_dependency(Kind::Build, Some(cfg), k, o, d))?;
+            inherit_dependencies(&mut target.dev_dependencies, workspace, workspace_base_path, &mut |k, o, d| provenance.add_dependency(Kind::Dev, Some(cfg), k, o, d))?;
         }
 
         if let Some(ws) = workspace {
-            self.lints.inherit(&ws.lints);
+            if !self.lints.is_set() {
+                self.lints.inherit(&ws.lints);
+                provenance.add_lints(&ws.lints);
+            }
         }
 
-        let package = match &mut self.package {
-            Some(p) => p,
-            None => return Ok(()),
-        };
-        if let Some(ws) = workspace.and_then(|w| w.package.as_ref()) {
-            Self::inherit_package_properties(package, ws, workspace_base_path)?;
+        if let Some(package) = &mut self.package {
+            if let Some(ws) = workspace.and_then(|w| w.package.as_ref()) {
+                Self::inherit_package_properties(package, ws, workspace_base_path, &mut provenance.package_fields)?;
+                provenance.snapshot_package_fields(package);
+            }
+
+            if package.needs_workspace_inheritance() {
+                return Err(Error::WorkspaceIntegrity(format!("not all fields of `{}` have been present in workspace.package", package.name())));
+            }
         }
 
-        if package.needs_workspace_inheritance() {
-            return Err(Error::WorkspaceIntegrity(format!("not all fields of `{}` have been present in workspace.package", package.name())));
+        if provenance.lints || !provenance.dependencies.is_empty() || !provenance.package_fields.is_empty() {
+            provenance.workspace_root = Some(workspace_base_path.into());
+            self.provenance.merge(provenance);
         }
         Ok(())
     }
 
-    fn inherit_package_properties(package: &mut Package<Metadata>, ws: &PackageTemplate, workspace_base_path: &Path) -> Result<(), Error> {
-        fn maybe_inherit<T: Clone>(to: Option<&mut Inheritable<T>>, from: Option<&T>) {
-            if let Some(from) = from {
-                if let Some(to) = to {
+    fn inherit_package_properties(package: &mut Package<Metadata>, ws: &PackageTemplate, workspace_base_path: &Path, inherited: &mut BTreeSet<String>) -> Result<(), Error> {
+        let mut maybe_inherit = |key: &str, to: Option<&mut Inheritable<_>>, from: Option<&String>| {
+            if let Some((to, from)) = to.zip(from) {
+                if !to.is_set() {
                     to.inherit(from);
+                    inherited.insert(key.into());
                 }
             }
-        }
-        fn inherit<T: Clone>(to: &mut Inheritable<T>, from: Option<&T>) {
+        };
+        maybe_inherit("description", package.description.as_mut(), ws.description.as_ref());
+        maybe_inherit("documentation", package.documentation.as_mut(), ws.documentation.as_ref());
+        maybe_inherit("homepage", package.homepage.as_mut(), ws.homepage.as_ref());
+        maybe_inherit("license", package.license.as_mut(), ws.license.as_ref());
+        maybe_inherit("repository", package.repository.as_mut(), ws.repository.as_ref());
+        maybe_inherit("rust-version", package.rust_version.as_mut(), ws.rust_version.as_ref());
+        maybe_inherit("version", Some(&mut package.version), ws.version.as_ref());
+
+        fn inherit<T: Clone>(key: &str, to: &mut Inheritable<T>, from: Option<&T>, inherited: &mut BTreeSet<String>) {
             if let Some(from) = from {
-                to.inherit(from);
+                if !to.is_set() {
+                    to.inherit(from);
+                    inherited.insert(key.into());
+                }
             }
         }
-        inherit(&mut package.authors, ws.authors.as_ref());
-        inherit(&mut package.categories, ws.categories.as_ref());
-        inherit(&mut package.edition, ws.edition.as_ref());
-        inherit(&mut package.exclude, ws.exclude.as_ref());
-        inherit(&mut package.include, ws.include.as_ref());
-        inherit(&mut package.keywords, ws.keywords.as_ref());
-        inherit(&mut package.version, ws.version.as_ref());
-        maybe_inherit(package.description.as_mut(), ws.description.as_ref());
-        maybe_inherit(package.documentation.as_mut(), ws.documentation.as_ref());
-        maybe_inherit(package.homepage.as_mut(), ws.homepage.as_ref());
-        maybe_inherit(package.license.as_mut(), ws.license.as_ref());
-        maybe_inherit(package.repository.as_mut(), ws.repository.as_ref());
-        maybe_inherit(package.rust_version.as_mut(), ws.rust_version.as_ref());
-        package.publish.inherit(&ws.publish);
+        inherit("authors", &mut package.authors, ws.authors.as_ref(), inherited);
+        inherit("categories", &mut package.categories, ws.categories.as_ref(), inherited);
+        inherit("edition", &mut package.edition, ws.edition.as_ref(), inherited);
+        inherit("exclude", &mut package.exclude, ws.exclude.as_ref(), inherited);
+        inherit("include", &mut package.include, ws.include.as_ref(), inherited);
+        inherit("keywords", &mut package.keywords, ws.keywords.as_ref(), inherited);
+        inherit("publish", &mut package.publish, Some(&ws.publish), inherited);
         match (&mut package.readme, &ws.readme) {
-            (r @ Inheritable::Inherited { .. }, flag @ OptionalFile::Flag(_)) => {
+            (r @ Inheritable::Inherited, flag @ OptionalFile::Flag(_)) => {
                 r.set(flag.clone());
+                inherited.insert("readme".into());
             },
-            (r @ Inheritable::Inherited { .. }, OptionalFile::Path(path)) => {
+            (r @ Inheritable::Inherited, OptionalFile::Path(path)) => {
                 r.set(OptionalFile::Path(workspace_base_path.join(path)));
+                inherited.insert("readme".into());
             },
             _ => {},
         }
         if let Some((f, ws)) = package.license_file.as_mut().zip(ws.license_file.as_ref()) {
-            f.set(workspace_base_path.join(ws))
+            if !f.is_set() {
+                f.set(workspace_base_path.join(ws));
+                inherited.insert("license-file".into());
+            }
         }
         Ok(())
     }
@@ -505,6 +596,7 @@ impl<Metadata> Manifest<Metadata> {
             Err(err) => return Err(err.into()),
         };
 
+        let edition = *package.edition.get()?;
         let has_path = self.lib.as_ref().is_some_and(|l| l.path.is_some());
         if !has_path && (package.autolib || self.lib.is_some()) && src.contains("lib.rs") {
             self.lib
@@ -514,64 +606,26 @@ impl<Metadata> Manifest<Metadata> {
         if let Some(lib) = &mut self.lib {
             lib.name.get_or_insert_with(|| package.name.replace('-', "_"));
             if lib.edition.is_none() {
-                lib.edition = Some(*package.edition.get()?);
+                lib.edition = Some(edition);
             }
             if lib.crate_type.is_empty() {
-                lib.crate_type.push("lib".to_string());
+                lib.crate_type.push(CrateType::Lib);
             }
             lib.required_features.clear(); // not applicable
         }
 
-        if package.autobins {
-            let mut bin = take(&mut self.bin);
-            let (fully_overrided, mut partial_overrided) = self.autoset(&mut bin, "src/bin", fs)?;
-            self.bin = bin;
-
-            if src.contains("main.rs") && !fully_overrided.contains("src/main.rs") {
-                let rel_path = "src/main.rs".to_string();
-                let name = &package.name;
-
-                let product = if let Some(mut product) = partial_overrided.remove(name) {
-                    product.path = Some(rel_path);
-                    product
-                } else {
-                    Product {
-                        name: Some(name.clone()),
-                        path: Some(rel_path),
-                        edition: Some(*package.edition.get()?),
-                        ..Product::default()
-                    }
-                };
-                self.bin.push(product);
+        for kind in [ProductKind::Bin, ProductKind::Example, ProductKind::Test, ProductKind::Bench] {
+            let (mut products, _) = self.discover_products(kind, edition, fs)?;
+            Self::sort_products(&mut products);
+            match kind {
+                ProductKind::Bin => self.bin = products,
+                ProductKind::Example => self.example = products,
+                ProductKind::Test => self.test = products,
+                ProductKind::Bench => self.bench = products,
+                ProductKind::Lib | ProductKind::BuildScript => {},
             }
         }
 
-        Self::sort_products(&mut self.bin);
-
-        if package.autoexamples {
-            let mut example = take(&mut self.example);
-            self.autoset(&mut example, "examples", fs)?;
-            self.example = example;
-        }
-
-        Self::sort_products(&mut self.example);
-
-        if package.autotests {
-            let mut test = take(&mut self.test);
-            self.autoset(&mut test, "tests", fs)?;
-            self.test = test;
-        }
-
-        Self::sort_products(&mut self.test);
-
-        if package.autobenches {
-            let mut bench = take(&mut self.bench);
-            self.autoset(&mut bench, "benches", fs)?;
-            self.bench = bench;
-        }
-
-        Self::sort_products(&mut self.bench);
-
         let Some(package) = &mut self.package else { return Ok(()) };
 
         let root_files = fs.file_names_in("")?;
@@ -588,77 +642,6 @@ impl<Metadata> Manifest<Metadata> {
         Ok(())
     }
 
-    /// Return the set of path overrided in `Cargo.toml`.
-    fn autoset(
-        &self,
-        out: &mut Vec<Product>,
-        dir: &str,
-        fs: &dyn AbstractFilesystem,
-    ) -> Result<(BTreeSet<String>, BTreeMap<String, Product>), Error> {
-        let fully_overrided: BTreeSet<_> = out.iter()
-            .filter_map(|product| product.path.clone())
-            .collect();
-
-        let mut partial_overrided: BTreeMap<String, Product> = out.iter()
-            .filter_map(|product| {
-                match (&product.path, &product.name)  {
-                    (None, Some(name)) => {
-                        Some((name.clone(), product.clone()))
-                    },
-                    _ => None
-                }
-            })
-            .collect();
-
-        // Remove partially overrided items
-        out.retain(|product| product.path.is_some());
-
-        if let Some(package) = &self.package {
-            if let Ok(bins) = fs.file_names_in(dir) {
-                for name in bins {
-                    let rel_path = format!("{dir}/{name}");
-
-                    if name.ends_with(".rs") {
-                        if !fully_overrided.contains(&rel_path) {
-                            let name = name.trim_end_matches(".rs");
-
-                            let product = if let Some(mut product) = partial_overrided.remove(name) {
-                                product.path = Some(rel_path);
-                                product
-                            } else {
-                                Product {
-                                    name: Some(name.to_string()),
-                                    path: Some(rel_path),
-                                    edition: Some(*package.edition.get()?),
-                                    ..Product::default()
-                                }
-                            };
-                            out.push(product);
-                        }
-                    } else if let Ok(sub) = fs.file_names_in(&rel_path) {
-                        let rel_path = format!("{rel_path}/main.rs");
-
-                        if sub.contains("main.rs") && !fully_overrided.contains(&rel_path) {
-                            let product = if let Some(mut product) = partial_overrided.remove(&*name) {
-                                product.path = Some(rel_path);
-                                product
-                            } else {
-                                Product {
-                                    name: Some(name.into()),
-                                    path: Some(rel_path),
-                                    edition: Some(*package.edition.get()?),
-                                    ..Product::default()
-                                }
-                            };
-                            out.push(product);
-                        }
-                    }
-                }
-            }
-        }
-        Ok((fully_overrided, partial_overrided))
-    }
-
     /// ensure bins are deterministic
     fn sort_products(products: &mut [Product]) {
         products.sort_unstable_by(|a, b| a.name.cmp(&b.name).then(a.path.cmp(&b.path)));
@@ -683,9 +666,10 @@ impl<Metadata> Manifest<Metadata> {
     }
 }
 
-fn inherit_dependencies<Ignored>(deps_to_inherit: &mut BTreeMap<String, Dependency>, workspace: Option<&Workspace<Ignored>>, workspace_base_path: &Path) -> Result<(), Error> {
+fn inherit_dependencies<Ignored>(deps_to_inherit: &mut BTreeMap<String, Dependency>, workspace: Option<&Workspace<Ignored>>, workspace_base_path: &Path, inherited: &mut dyn FnMut(&str, InheritedDependencyDetail, &Dependency)) -> Result<(), Error> {
     for (key, dep) in deps_to_inherit {
         if let Dependency::Inherited(overrides) = dep {
+            let original = overrides.clone();
             let template = workspace.and_then(|ws| ws.dependencies.get(key))
                 .ok_or_else(|| Error::WorkspaceIntegrity(format!("workspace dependencies are missing `{key}`")))?;
             let mut overrides = overrides.clone();
@@ -702,6 +686,7 @@ fn inherit_dependencies<Ignored>(deps_to_inherit: &mut BTreeMap<String, Dependen
                     *path = workspace_base_path.join(&path).display().to_string();
                 }
             }
+            inherited(key, original, dep);
         }
     }
     Ok(())
@@ -728,12 +713,16 @@ impl<Metadata: Default> Default for Manifest<Metadata> {
             test: Default::default(),
             example: Default::default(),
             lints: Default::default(),
+            provenance: Default::default(),
+            extra: Default::default(),
+            explicit: Default::default(),
         }
     }
 }
 
 /// Build-in an custom build/optimization settings
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Profiles {
     /// Used for `--release`
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -770,6 +759,31 @@ impl Profiles {
             && self.doc.is_none()
             && self.custom.is_empty()
     }
+
+    /// Profile by its name, e.g. `"release"` or a custom one
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        match name {
+            "release" => self.release.as_ref(),
+            "dev" => self.dev.as_ref(),
+            "test" => self.test.as_ref(),
+            "bench" => self.bench.as_ref(),
+            "doc" => self.doc.as_ref(),
+            _ => self.custom.get(name),
+        }
+    }
+
+    /// Profile by its name, e.g. `"release"` or a custom one
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        match name {
+            "release" => self.release.as_mut(),
+            "dev" => self.dev.as_mut(),
+            "test" => self.test.as_mut(),
+            "bench" => self.bench.as_mut(),
+            "doc" => self.doc.as_mut(),
+            _ => self.custom.get_mut(name),
+        }
+    }
 }
 
 /// Verbosity of debug info in a [`Profile`]
@@ -897,13 +911,148 @@ impl TryFrom<Value> for LtoSetting {
     }
 }
 
+/// Optimization level in a build profile
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub enum OptLevel {
+    /// 0, no optimizations
+    O0,
+    /// 1, basic optimizations
+    O1,
+    /// 2, some optimizations
+    O2,
+    /// 3, all optimizations
+    O3,
+    /// "s", optimize for binary size
+    S,
+    /// "z", optimize for binary size, and turn off loop vectorization
+    Z,
+}
+
+impl Serialize for OptLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::O0 => serializer.serialize_i8(0),
+            Self::O1 => serializer.serialize_i8(1),
+            Self::O2 => serializer.serialize_i8(2),
+            Self::O3 => serializer.serialize_i8(3),
+            Self::S => serializer.serialize_str("s"),
+            Self::Z => serializer.serialize_str("z"),
+        }
+    }
+}
+
+impl TryFrom<Value> for OptLevel {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self, Error> {
+        Ok(match v {
+            Value::Integer(n) => match n {
+                0 => Self::O0,
+                1 => Self::O1,
+                2 => Self::O2,
+                3 => Self::O3,
+                _ => return Err(Error::Other("opt-level must be 0-3, \"s\" or \"z\"")),
+            },
+            Value::String(s) => match s.as_str() {
+                "0" => Self::O0,
+                "1" => Self::O1,
+                "2" => Self::O2,
+                "3" => Self::O3,
+                "s" => Self::S,
+                "z" => Self::Z,
+                _ => return Err(Error::Other("opt-level must be 0-3, \"s\" or \"z\"")),
+            },
+            _ => return Err(Error::Other("wrong data type for opt-level")),
+        })
+    }
+}
+
+/// Handling of panics in a build profile
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "toml::Value")]
+#[non_exhaustive]
+pub enum PanicStrategy {
+    /// "unwind"
+    Unwind,
+    /// "abort"
+    Abort,
+    /// "immediate-abort" (unstable)
+    ImmediateAbort,
+}
+
+impl Serialize for PanicStrategy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Self::Unwind => "unwind",
+            Self::Abort => "abort",
+            Self::ImmediateAbort => "immediate-abort",
+        })
+    }
+}
+
+impl TryFrom<Value> for PanicStrategy {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self, Error> {
+        Ok(match v {
+            Value::String(s) => match s.as_str() {
+                "unwind" => Self::Unwind,
+                "abort" => Self::Abort,
+                "immediate-abort" => Self::ImmediateAbort,
+                _ => return Err(Error::Other("panic must be \"unwind\", \"abort\" or \"immediate-abort\"")),
+            },
+            _ => return Err(Error::Other("wrong data type for panic setting")),
+        })
+    }
+}
+
+/// Handling of debug info files in a build profile
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "toml::Value")]
+pub enum SplitDebuginfo {
+    /// "off", debug info is in the executable
+    Off,
+    /// "packed", a single separate file (`.dSYM`, `.pdb`, `.dwp`)
+    Packed,
+    /// "unpacked", multiple separate files
+    Unpacked,
+}
+
+impl Serialize for SplitDebuginfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            Self::Off => "off",
+            Self::Packed => "packed",
+            Self::Unpacked => "unpacked",
+        })
+    }
+}
+
+impl TryFrom<Value> for SplitDebuginfo {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self, Error> {
+        Ok(match v {
+            Value::String(s) => match s.as_str() {
+                "off" => Self::Off,
+                "packed" => Self::Packed,
+                "unpacked" => Self::Unpacked,
+                _ => return Err(Error::Other("split-debuginfo must be \"off\", \"packed\", or \"unpacked\"")),
+            },
+            _ => return Err(Error::Other("wrong data type for split-debuginfo setting")),
+        })
+    }
+}
+
 /// Compilation/optimization settings for a workspace
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct Profile {
     /// num or z, s
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub opt_level: Option<Value>,
+    pub opt_level: Option<OptLevel>,
 
     /// 0,1,2 or bool
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -911,7 +1060,7 @@ pub struct Profile {
 
     /// Move debug info to separate files
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub split_debuginfo: Option<String>,
+    pub split_debuginfo: Option<SplitDebuginfo>,
 
     /// For dynamic libraries
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -931,7 +1080,7 @@ pub struct Profile {
 
     /// Handling of panics/unwinding
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub panic: Option<String>,
+    pub panic: Option<PanicStrategy>,
 
     /// Support for incremental rebuilds
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -945,20 +1094,145 @@ pub struct Profile {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub strip: Option<StripSetting>,
 
-    /// Profile overrides for dependencies, `*` is special.
+    /// Profile overrides for dependencies, `*` is special. Keys can be `name` or `name@version`.
+    ///
+    /// See [`Profile::package_override`].
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
-    pub package: BTreeMap<String, Value>,
+    pub package: BTreeMap<String, ProfileOverride>,
 
-    /// Profile overrides for build dependencies, `*` is special.
+    /// Profile overrides for build scripts, proc macros, and their dependencies
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub build_override: Option<Value>,
+    pub build_override: Option<ProfileOverride>,
 
     /// Only relevant for non-standard profiles
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub inherits: Option<String>,
+
+    /// Keys that this crate doesn't support, kept to be serialized back
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
+    pub extra: BTreeMap<String, Value>,
+
+    /// Which keys of this table have been written in `Cargo.toml`, as opposed to filled in with defaults
+    #[serde(skip)]
+    pub explicit: ExplicitKeys,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+impl Profile {
+    /// The `[profile.….package.<spec>]` override that applies to this dependency.
+    ///
+    /// `name@version` is preferred over `name`, which is preferred over `*`.
+    /// Versions in keys can be partial, e.g. `foo@1` matches `foo` 1.2.3.
+    /// Note that Cargo doesn't apply `*` to workspace members.
+    #[must_use]
+    pub fn package_override(&self, name: &str, version: Option<&str>) -> Option<&ProfileOverride> {
+        let mut best = None;
+        for (spec, o) in &self.package {
+            let rank = match spec.split_once(['@', ':']) {
+                _ if spec == "*" => 1,
+                None if spec == name => 2,
+                Some((n, v)) if n == name && version.is_some_and(|version| version_matches(v, version)) => 3,
+                _ => continue,
+            };
+            if best.as_ref().map_or(true, |&(r, _)| rank > r) {
+                best = Some((rank, o));
+            }
+        }
+        best.map(|(_, o)| o)
+    }
+}
+
+/// `1.2` matches `1.2.3`, ignoring `+build` metadata
+fn version_matches(spec: &str, version: &str) -> bool {
+    let version = version.split('+').next().unwrap_or_default();
+    let spec = spec.split('+').next().unwrap_or_default();
+    let (spec_num, spec_pre) = spec.split_once('-').map_or((spec, None), |(n, p)| (n, Some(p)));
+    let (num, pre) = version.split_once('-').map_or((version, None), |(n, p)| (n, Some(p)));
+    let mut num = num.split('.');
+    spec_num.split('.').all(|s| num.next() == Some(s)) && spec_pre.map_or(true, |p| pre == Some(p))
+}
+
+/// Settings in `[profile.….package.<spec>]` and `[profile.….build-override]`.
+///
+/// These are the same as in [`Profile`], except ones that Cargo doesn't allow to override per package:
+/// `panic`, `lto`, `rpath`, `inherits`, and nested `package` and `build-override`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "kebab-case", try_from = "toml::Value")]
+#[non_exhaustive]
+pub struct ProfileOverride {
+    /// num or z, s
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opt_level: Option<OptLevel>,
+
+    /// 0,1,2 or bool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug: Option<DebugSetting>,
+
+    /// Move debug info to separate files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_debuginfo: Option<SplitDebuginfo>,
+
+    /// Extra assertions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub debug_assertions: Option<bool>,
+
+    /// Parallel compilation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codegen_units: Option<u16>,
+
+    /// Support for incremental rebuilds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incremental: Option<bool>,
+
+    /// Check integer arithmetic
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overflow_checks: Option<bool>,
+
+    /// Remove debug info
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip: Option<StripSetting>,
+
+    /// Keys that this crate doesn't support, kept to be serialized back
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl TryFrom<Value> for ProfileOverride {
+    type Error = Error;
+
+    fn try_from(v: Value) -> Result<Self, Error> {
+        let table = v.as_table().ok_or(Error::Other("wrong data type for profile override"))?;
+        for key in table.keys() {
+            return Err(Error::Other(match key.as_str() {
+                "panic" => "`panic` can't be overridden per package",
+                "lto" => "`lto` can't be overridden per package",
+                "rpath" => "`rpath` can't be overridden per package",
+                "inherits" => "`inherits` can't be used in a package override",
+                "package" => "package overrides can't be nested",
+                "build-override" => "`build-override` can't be nested",
+                _ => continue,
+            }));
+        }
+        // reuses parsing of the profile's fields
+        let p = Profile::deserialize(v).map_err(|e| Error::Parse(Box::new(e)))?;
+        Ok(Self {
+            opt_level: p.opt_level,
+            debug: p.debug,
+            split_debuginfo: p.split_debuginfo,
+            debug_assertions: p.debug_assertions,
+            codegen_units: p.codegen_units,
+            incremental: p.incremental,
+            overflow_checks: p.overflow_checks,
+            strip: p.strip,
+            extra: p.extra,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 /// Cargo uses the term "target" for both "target platform" and "build target" (the thing to build),
 /// which makes it ambigous.
@@ -1017,8 +1291,9 @@ pub struct Product {
     pub edition: Option<Edition>,
 
     /// The available options are "dylib", "rlib", "staticlib", "cdylib", and "proc-macro".
+    /// Completion sets it to `lib` for libraries that don't specify it.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub crate_type: Vec<String>,
+    pub crate_type: Vec<CrateType>,
 
     /// The `required-features` field specifies which features the product needs in order to be built.
     /// If any of the required features are not selected, the product will be skipped.
@@ -1026,6 +1301,15 @@ pub struct Product {
     /// it has no effect on `[lib]`.
     #[serde(default)]
     pub required_features: Vec<String>,
+
+    /// Keys that this crate doesn't support, kept to be serialized back
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
+    pub extra: BTreeMap<String, Value>,
+
+    /// Which keys of this table have been written in `Cargo.toml`, as opposed to filled in with defaults
+    #[serde(skip)]
+    pub explicit: ExplicitKeys,
 }
 
 impl Default for Product {
@@ -1043,12 +1327,129 @@ impl Default for Product {
             required_features: Vec::new(),
             crate_type: Vec::new(),
             edition: None,
+            extra: BTreeMap::new(),
+            explicit: ExplicitKeys::default(),
+        }
+    }
+}
+
+/// Kind of artifact built from a [`Product`], set in `crate-type`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(from = "String")]
+#[non_exhaustive]
+pub enum CrateType {
+    /// "bin", an executable
+    Bin,
+    /// "lib", the default kind of library (currently `rlib`)
+    Lib,
+    /// "rlib", a Rust library
+    Rlib,
+    /// "dylib", a dynamic Rust library
+    Dylib,
+    /// "cdylib", a dynamic library with a C interface
+    Cdylib,
+    /// "staticlib", a static library with a C interface
+    Staticlib,
+    /// "proc-macro", a procedural macro library
+    ProcMacro,
+    /// Not supported by this crate, kept as-is
+    Other(String),
+}
+
+impl CrateType {
+    /// Value for `crate-type`, like `"cdylib"`
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Bin => "bin",
+            Self::Lib => "lib",
+            Self::Rlib => "rlib",
+            Self::Dylib => "dylib",
+            Self::Cdylib => "cdylib",
+            Self::Staticlib => "staticlib",
+            Self::ProcMacro => "proc-macro",
+            Self::Other(s) => s,
+        }
+    }
+
+    /// Produces a library that can be linked from C and other languages (`cdylib` or `staticlib`)
+    #[must_use]
+    pub fn is_c_linkable(&self) -> bool {
+        matches!(self, Self::Cdylib | Self::Staticlib)
+    }
+
+    /// Produces a library that can be used by other Rust crates (`lib`, `rlib`, or `dylib`)
+    #[must_use]
+    pub fn is_rust_linkable(&self) -> bool {
+        matches!(self, Self::Lib | Self::Rlib | Self::Dylib)
+    }
+
+    /// Name of the file built from a crate `name` for `target_os` (a value like [`std::env::consts::OS`]).
+    ///
+    /// `name` is the crate name (with `_`) for libraries, and the target name for binaries. `None` for unknown crate types.
+    #[must_use]
+    pub fn file_name(&self, name: &str, target_os: &str) -> Option<String> {
+        let windows = target_os == "windows";
+        let dylib_ext = match target_os {
+            "windows" => "dll",
+            "macos" | "ios" => "dylib",
+            _ => "so",
+        };
+        let lib_prefix = if windows { "" } else { "lib" };
+        Some(match self {
+            Self::Bin if windows => format!("{name}.exe"),
+            Self::Bin => name.to_string(),
+            Self::Lib | Self::Rlib => format!("lib{name}.rlib"),
+            Self::Dylib | Self::Cdylib | Self::ProcMacro => format!("{lib_prefix}{name}.{dylib_ext}"),
+            Self::Staticlib if windows => format!("{name}.lib"),
+            Self::Staticlib => format!("lib{name}.a"),
+            Self::Other(_) => return None,
+        })
+    }
+
+    /// Can't be combined with `proc-macro = true`. `lib` is an exception, since proc-macro crates are libraries too.
+    #[must_use]
+    pub fn conflicts_with_proc_macro(&self) -> bool {
+        !matches!(self, Self::ProcMacro | Self::Lib)
+    }
+}
+
+impl From<String> for CrateType {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "bin" => Self::Bin,
+            "lib" => Self::Lib,
+            "rlib" => Self::Rlib,
+            "dylib" => Self::Dylib,
+            "cdylib" => Self::Cdylib,
+            "staticlib" => Self::Staticlib,
+            "proc-macro" => Self::ProcMacro,
+            _ => Self::Other(s),
         }
     }
 }
 
+impl From<&str> for CrateType {
+    fn from(s: &str) -> Self {
+        s.to_string().into()
+    }
+}
+
+impl Serialize for CrateType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Display for CrateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Dependencies that are platform-specific or enabled through custom `cfg()`.
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct Target {
     /// platform-specific normal deps
@@ -1060,12 +1461,34 @@ pub struct Target {
     /// platform-specific build-time deps
     #[serde(default)]
     pub build_dependencies: DepsSet,
+
+    /// Keys that this crate doesn't support, kept to be serialized back
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
+    pub extra: BTreeMap<String, Value>,
+
+    /// Which keys of this table have been written in `Cargo.toml`, as opposed to filled in with defaults
+    #[serde(skip)]
+    pub explicit: ExplicitKeys,
+}
+
+/// Section of the manifest the dependency is in.
+///
+/// Serializes as `"normal"`, `"build"`, or `"dev"`, like in the crates.io index.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    #[default]
+    Normal,
+    Build,
+    Dev,
 }
 
 /// Dependency definition. Note that this struct doesn't carry it's key/name, which you need to read from its section.
 ///
 /// It can be simple version number, or detailed settings, or inherited.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Dependency {
     /// Version requirement (e.g. `^1.5`)
@@ -1211,6 +1634,7 @@ impl Dependency {
 
 /// When definition of a dependency is more than just a version string.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct DependencyDetail {
     /// Semver requirement. Note that a plain version number implies this version *or newer* compatible one.
@@ -1279,6 +1703,7 @@ pub struct DependencyDetail {
 
     /// Contains the remaining unstable keys and values for the dependency.
     #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
     pub unstable: BTreeMap<String, Value>,
 }
 
@@ -1306,6 +1731,7 @@ impl Default for DependencyDetail {
 /// When a dependency is defined as `{ workspace = true }`,
 /// and workspace data hasn't been applied yet.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct InheritedDependencyDetail {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -1325,6 +1751,8 @@ pub struct InheritedDependencyDetail {
 /// You can replace `Metadata` generic type with your own
 /// to parse into something more useful than a generic toml `Value`
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "schemars", schemars(rename = "Package", bound = ""))]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct Package<Metadata = Value> {
@@ -1456,11 +1884,27 @@ pub struct Package<Metadata = Value> {
 
     /// Arbitrary metadata of any type, an extension point for 3rd party tools.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<crate::schema::AnyValue>"))]
     pub metadata: Option<Metadata>,
+    /// Keys that this crate doesn't support, kept to be serialized back
+    #[serde(flatten)]
+    #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
+    pub extra: BTreeMap<String, Value>,
+
+    /// Which keys of this table have been written in `Cargo.toml`, as opposed to filled in with defaults
+    #[serde(skip)]
+    pub explicit: ExplicitKeys,
 }
 
 #[allow(deprecated)]
 impl<Metadata> Package<Metadata> {
+    /// Cargo's rule that a missing version makes `publish` default to `false`
+    pub(crate) fn set_publish_default(&mut self) {
+        if !self.explicit.contains("version") && !self.explicit.contains("publish") {
+            self.publish = Inheritable::Set(Publish::Flag(false));
+        }
+    }
+
     /// Prefer creating it by parsing a [`Manifest`] instead.
     pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
         Self {
@@ -1492,6 +1936,8 @@ impl<Metadata> Package<Metadata> {
             publish: Inheritable::Set(Publish::Flag(true)),
             resolver: None,
             metadata: None,
+            extra: BTreeMap::new(),
+            explicit: ExplicitKeys::default(),
         }
     }
 
@@ -1726,6 +2172,7 @@ impl<Metadata: Default> Default for Package<Metadata> {
 
 /// A way specify or disable README or `build.rs`.
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged, expecting = "the value should be either a boolean or a file path")]
 pub enum OptionalFile {
     /// Opt-in to default, or explicit opt-out
@@ -1772,6 +2219,7 @@ impl OptionalFile {
 
 /// Forbids or selects custom registry
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged, expecting = "the value should be either a boolean, or an array of registry names")]
 pub enum Publish {
     Flag(bool),
@@ -1824,6 +2272,7 @@ impl PartialEq<&Publish> for bool {
 ///
 /// Mostly obsolete.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct Badge {
     pub repository: String,
@@ -1852,6 +2301,7 @@ fn default_version() -> Inheritable<String> {
 
 /// `[badges]` section of `Cargo.toml`, deprecated by crates-io except `maintenance`.
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub struct Badges {
     /// Appveyor: `repository` is required. `branch` is optional; default is `master`
@@ -1930,6 +2380,7 @@ impl Badges {
 /// maintenance.status = "experimental"
 /// ```
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Maintenance {
     pub status: MaintenanceStatus,
 }
@@ -1943,6 +2394,7 @@ pub struct Maintenance {
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 #[derive(Default)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum MaintenanceStatus {
     #[default]
     None,
@@ -1956,6 +2408,7 @@ pub enum MaintenanceStatus {
 
 /// Edition setting, which opts in to new Rust/Cargo behaviors.
 #[derive(Debug, Default, PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[non_exhaustive]
 #[serde(expecting = "if there's a newer edition, then this parser (cargo_toml crate) has to be updated")]
 pub enum Edition {
@@ -1996,12 +2449,23 @@ impl Edition {
             Edition::E2024 => 85,
         }
     }
+
+    /// The feature resolver used by packages that don't set `resolver` explicitly
+    #[must_use]
+    pub fn default_resolver(self) -> Resolver {
+        match self {
+            Edition::E2015 | Edition::E2018 => Resolver::V1,
+            Edition::E2021 => Resolver::V2,
+            Edition::E2024 => Resolver::V3,
+        }
+    }
 }
 
 /// The feature resolver version.
 ///
 /// Needed in [`Workspace`], but implied by [`Edition`] in packages.
 #[derive(Debug, Default, PartialEq, Eq, Ord, PartialOrd, Copy, Clone, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(expecting = "if there's a newer resolver, then this parser (cargo_toml crate) has to be updated")]
 pub enum Resolver {
     #[serde(rename = "1")]
@@ -2042,6 +2506,7 @@ pub struct Lint {
 
 /// Internal
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(untagged, expecting = "lints' values should be a string or { level = \"…\", priority = 1 }")]
 enum LintSerdeParser {
     Simple(LintLevel),
@@ -2053,6 +2518,7 @@ enum LintSerdeParser {
 
         /// Unstable
         #[serde(default, flatten)]
+        #[cfg_attr(feature = "schemars", schemars(with = "BTreeMap<String, crate::schema::AnyValue>"))]
         config: BTreeMap<String, toml::Value>,
     },
 }
@@ -2078,6 +2544,7 @@ impl From<Lint> for LintSerdeParser {
 
 /// Lint level.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename_all = "kebab-case")]
 pub enum LintLevel {
     Allow,
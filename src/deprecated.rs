@@ -0,0 +1,176 @@
+use crate::{Error, Value};
+use std::fmt;
+
+/// Use of an obsolete key or section in `Cargo.toml`. See [`Deprecation::find`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Deprecation {
+    /// Legacy spelling of a key, like `dev_dependencies`. It's an error in the 2024 edition.
+    Renamed {
+        /// Keys of the tables leading to it, e.g. `["dependencies", "serde", "default_features"]`. Arrays of tables use indices, e.g. `["bin", "0", "crate_type"]`.
+        path: Vec<String>,
+        /// The current spelling
+        new_key: &'static str,
+    },
+    /// `[replace]` has been superseded by `[patch]`
+    Replace,
+    /// crates.io doesn't display badges other than `maintenance`
+    Badge { name: String },
+    /// `authors` is deprecated, and not displayed on crates.io. The path is `["package", "authors"]` or `["workspace", "package", "authors"]`.
+    Authors { path: Vec<String> },
+}
+
+impl Deprecation {
+    /// Can be fixed automatically by `Deprecation::migrate` (requires the `edit` feature)
+    #[must_use]
+    pub fn is_migratable(&self) -> bool {
+        matches!(self, Self::Renamed { .. } | Self::Badge { .. })
+    }
+
+    /// Finds legacy key spellings, `[replace]`, deprecated `[badges]`, and `authors`.
+    ///
+    /// It needs the source of `Cargo.toml`, because the parsed [`Manifest`](crate::Manifest) accepts some of the old spellings, and doesn't keep track of them.
+    pub fn find(cargo_toml_content: &str) -> Result<Vec<Self>, Error> {
+        let root: toml::Table = toml::from_str(cargo_toml_content)?;
+        let mut out = Vec::new();
+        let mut path = Vec::new();
+        find_renamed(&root, Context::Root, &mut path, &mut out);
+
+        if root.contains_key("replace") {
+            out.push(Self::Replace);
+        }
+        for name in root.get("badges").and_then(Value::as_table).into_iter().flat_map(|b| b.keys()) {
+            if name != "maintenance" {
+                out.push(Self::Badge { name: name.clone() });
+            }
+        }
+        let package = root.get("package").or_else(|| root.get("project"));
+        if package.and_then(|p| p.get("authors")).is_some() {
+            out.push(Self::Authors { path: vec!["package".into(), "authors".into()] });
+        }
+        if root.get("workspace").and_then(|w| w.get("package")).and_then(|p| p.get("authors")).is_some() {
+            out.push(Self::Authors { path: vec!["workspace".into(), "package".into(), "authors".into()] });
+        }
+        out.sort_unstable();
+        Ok(out)
+    }
+
+    /// Rewrites `Cargo.toml` to use current key spellings, and removes deprecated badges, preserving formatting and comments.
+    ///
+    /// Returns the new source, and the deprecations that need to be fixed manually.
+    /// Keys are not renamed if the new spelling is already used in the same table.
+    #[cfg(feature = "edit")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "edit")))]
+    pub fn migrate(cargo_toml_content: &str) -> Result<(String, Vec<Self>), Error> {
+        let found = Self::find(cargo_toml_content)?;
+        let mut doc: toml_edit::DocumentMut = cargo_toml_content.parse().map_err(|_| Error::Other("can't parse the manifest for editing"))?;
+
+        // renaming the deepest keys first keeps the paths of the other keys valid
+        let mut renames: Vec<_> = found.iter().filter_map(|d| match d {
+            Self::Renamed { path, new_key } => Some((path, *new_key)),
+            _ => None,
+        }).collect();
+        renames.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+
+        let mut remaining = Vec::new();
+        for (path, new_key) in renames {
+            let Some((old_key, parent)) = path.split_last() else { continue };
+            let renamed = crate::edit::item_mut(doc.as_item_mut(), parent)
+                .and_then(toml_edit::Item::as_table_like_mut)
+                .is_some_and(|t| crate::edit::rename_key(t, old_key, new_key));
+            if !renamed {
+                remaining.push(Self::Renamed { path: path.clone(), new_key });
+            }
+        }
+        for d in &found {
+            match d {
+                Self::Badge { name } => {
+                    let badges = doc.get_mut("badges").and_then(toml_edit::Item::as_table_like_mut);
+                    if let Some(badges) = badges {
+                        badges.remove(name);
+                        if badges.is_empty() {
+                            doc.remove("badges");
+                        }
+                    }
+                },
+                Self::Renamed { .. } => {},
+                other => remaining.push(other.clone()),
+            }
+        }
+        remaining.sort_unstable();
+        Ok((doc.to_string(), remaining))
+    }
+}
+
+impl fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Renamed { path, new_key } => write!(f, "`{}` is deprecated; use `{new_key}` instead", path.join(".")),
+            Self::Replace => f.write_str("[replace] is deprecated; use [patch] instead"),
+            Self::Badge { name } => write!(f, "badge `{name}` is not displayed on crates.io"),
+            Self::Authors { path } => write!(f, "`{}` is deprecated", path.join(".")),
+        }
+    }
+}
+
+/// Kind of table, to know which keys have legacy spellings
+#[derive(Clone, Copy)]
+enum Context {
+    Root,
+    Target,
+    Dependencies,
+    Dependency,
+    Product,
+    Other,
+}
+
+impl Context {
+    fn renamed(self, key: &str) -> Option<&'static str> {
+        Some(match (self, key) {
+            (Self::Root, "project") => "package",
+            (Self::Root | Self::Target, "dev_dependencies") => "dev-dependencies",
+            (Self::Root | Self::Target, "build_dependencies") => "build-dependencies",
+            (Self::Dependency, "default_features") => "default-features",
+            (Self::Product, "proc_macro") => "proc-macro",
+            (Self::Product, "crate_type") => "crate-type",
+            _ => return None,
+        })
+    }
+
+    fn nested(self, key: &str) -> Self {
+        match (self, key) {
+            (Self::Root, "lib" | "bin" | "example" | "test" | "bench") => Self::Product,
+            (Self::Root | Self::Target, "dependencies" | "dev-dependencies" | "dev_dependencies" | "build-dependencies" | "build_dependencies") => Self::Dependencies,
+            (Self::Dependencies, _) => Self::Dependency,
+            _ => Self::Other,
+        }
+    }
+}
+
+fn find_renamed(table: &toml::Table, context: Context, path: &mut Vec<String>, out: &mut Vec<Deprecation>) {
+    for (key, value) in table {
+        path.push(key.clone());
+        if let Some(new_key) = context.renamed(key) {
+            out.push(Deprecation::Renamed { path: path.clone(), new_key });
+        }
+        // `[target.'cfg(…)']`, `[workspace.dependencies]`, and `[patch.registry]` depend on the parent's key
+        let nested = match path.as_slice() {
+            [t, _] if t == "target" => Context::Target,
+            [ws, deps] if ws == "workspace" && deps == "dependencies" => Context::Dependencies,
+            [p, _] if p == "patch" => Context::Dependencies,
+            _ => context.nested(key),
+        };
+        match value {
+            Value::Table(t) => find_renamed(t, nested, path, out),
+            Value::Array(items) => for (i, item) in items.iter().enumerate() {
+                if let Value::Table(t) = item {
+                    path.push(i.to_string());
+                    find_renamed(t, nested, path, out);
+                    path.pop();
+                }
+            },
+            _ => {},
+        }
+        path.pop();
+    }
+}
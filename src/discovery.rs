@@ -0,0 +1,162 @@
+use crate::{AbstractFilesystem, Edition, Error, Manifest, Product, ProductKind};
+use std::fmt;
+use std::io;
+
+/// A file that could have been autodiscovered as a product. See [`Manifest::autodiscovery_report`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct DiscoveryCandidate {
+    /// Section it would be added to
+    pub kind: ProductKind,
+    /// Name it would get, from the file name (or the directory name for `main.rs`)
+    pub name: String,
+    /// Relative to the package's directory, e.g. `src/bin/tool/main.rs`
+    pub path: String,
+    /// What happened to it
+    pub outcome: DiscoveryOutcome,
+}
+
+/// How a [`DiscoveryCandidate`] has been used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum DiscoveryOutcome {
+    /// Added as a new product
+    Inferred,
+    /// An explicit product has this path, so its settings are used
+    Overridden,
+    /// An explicit product with this name didn't have a path, and got this file as its path
+    Merged,
+    /// Not used
+    Ignored(IgnoredReason),
+}
+
+/// Why a [`DiscoveryCandidate`] hasn't been used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum IgnoredReason {
+    /// `autobins = false` (or `autoexamples`, `autotests`, `autobenches`)
+    Disabled,
+    /// In the 2015 edition, listing any product of a kind disables discovery of that kind, unless `autobins = true` (etc.) is set explicitly
+    Edition2015,
+    /// An explicit product with a different path already has this name
+    NameTaken,
+    /// Another file would be a product with the same name, e.g. `src/bin/foo.rs` and `src/bin/foo/main.rs`. Cargo refuses to pick one.
+    Ambiguous,
+}
+
+impl fmt::Display for DiscoveryCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { kind, name, path, .. } = self;
+        match self.outcome {
+            DiscoveryOutcome::Inferred => write!(f, "`{path}` is {kind} `{name}`"),
+            DiscoveryOutcome::Overridden => write!(f, "`{path}` is configured by an explicit [[{kind}]]"),
+            DiscoveryOutcome::Merged => write!(f, "`{path}` is the path of the explicit [[{kind}]] `{name}`"),
+            DiscoveryOutcome::Ignored(IgnoredReason::Disabled) => write!(f, "`{path}` is ignored, because auto-discovery of {kind} targets is disabled"),
+            DiscoveryOutcome::Ignored(IgnoredReason::Edition2015) => write!(f, "`{path}` is ignored, because in the 2015 edition explicit [[{kind}]] disable auto-discovery"),
+            DiscoveryOutcome::Ignored(IgnoredReason::NameTaken) => write!(f, "`{path}` is ignored, because another [[{kind}]] is named `{name}`"),
+            DiscoveryOutcome::Ignored(IgnoredReason::Ambiguous) => write!(f, "`{path}` is ignored, because another file would also be {kind} `{name}`"),
+        }
+    }
+}
+
+impl<Metadata> Manifest<Metadata> {
+    /// Explains which files in `src/bin`, `examples`, `tests`, and `benches` (and `src/main.rs`) are autodiscovered as products, and why.
+    ///
+    /// Use a manifest that hasn't been completed, because completion adds the discovered products to the manifest,
+    /// but apply workspace inheritance first if the edition is inherited.
+    ///
+    /// The 2015 edition rule needs to know whether `autobins` (etc.) has been written, which is known only for manifests parsed with [`Manifest::from_str`] and similar.
+    /// If [`Package::explicit`](crate::Package::explicit) is empty, `autobins = true` is assumed to be explicit.
+    pub fn autodiscovery_report<Fs: AbstractFilesystem>(&self, fs: Fs) -> Result<Vec<DiscoveryCandidate>, Error> {
+        let Some(package) = &self.package else { return Ok(Vec::new()) };
+        let edition = *package.edition.get()?;
+        let mut out = Vec::new();
+        for kind in [ProductKind::Bin, ProductKind::Example, ProductKind::Test, ProductKind::Bench] {
+            out.extend(self.discover_products(kind, edition, &fs)?.1);
+        }
+        Ok(out)
+    }
+
+    /// Explicit products of the kind, with paths filled in, and the autodiscovered ones, following Cargo's rules for the edition
+    pub(crate) fn discover_products(&self, kind: ProductKind, edition: Edition, fs: &dyn AbstractFilesystem) -> Result<(Vec<Product>, Vec<DiscoveryCandidate>), Error> {
+        let Some(package) = &self.package else { return Ok(Default::default()) };
+        let (listed, auto_key, auto, dir) = match kind {
+            ProductKind::Bin => (&self.bin, "autobins", package.autobins, "src/bin"),
+            ProductKind::Example => (&self.example, "autoexamples", package.autoexamples, "examples"),
+            ProductKind::Test => (&self.test, "autotests", package.autotests, "tests"),
+            ProductKind::Bench => (&self.bench, "autobenches", package.autobenches, "benches"),
+            ProductKind::Lib | ProductKind::BuildScript => return Ok((Vec::new(), Vec::new())),
+        };
+        let ignored = if !auto {
+            Some(IgnoredReason::Disabled)
+        } else if edition < Edition::E2018 && !listed.is_empty() && !package.explicit.is_empty() && !package.explicit.contains(auto_key) {
+            Some(IgnoredReason::Edition2015)
+        } else {
+            None
+        };
+
+        let mut candidates = discoverable(fs, dir)?;
+        if kind == ProductKind::Bin && fs.file_names_in("src").is_ok_and(|src| src.contains("main.rs")) {
+            candidates.push((package.name.clone(), "src/main.rs".into()));
+        }
+        candidates.sort_unstable_by(|a, b| a.1.cmp(&b.1));
+        let ambiguous: Vec<String> = candidates.iter().enumerate()
+            .filter(|&(i, (name, _))| candidates[i + 1..].iter().any(|(n, _)| n == name))
+            .map(|(_, (name, _))| name.clone()).collect();
+
+        // paths of explicit products are inferred from their names, even if discovery is disabled
+        let mut products = listed.clone();
+        let mut merged = Vec::new();
+        for p in products.iter_mut().filter(|p| p.path.is_none()) {
+            let Some(name) = &p.name else { continue };
+            if let Some((_, path)) = candidates.iter().find(|(n, _)| n == name && !ambiguous.contains(n)) {
+                p.path = Some(path.clone());
+                merged.push(path.clone());
+            }
+        }
+
+        let mut report = Vec::with_capacity(candidates.len());
+        for (name, path) in candidates {
+            let outcome = if merged.contains(&path) {
+                DiscoveryOutcome::Merged
+            } else if products.iter().any(|p| p.path.as_deref().is_some_and(|p| p.trim_start_matches("./") == path)) {
+                DiscoveryOutcome::Overridden
+            } else if ambiguous.contains(&name) {
+                DiscoveryOutcome::Ignored(IgnoredReason::Ambiguous)
+            } else if products.iter().any(|p| p.name.as_ref() == Some(&name)) {
+                DiscoveryOutcome::Ignored(IgnoredReason::NameTaken)
+            } else if let Some(reason) = ignored {
+                DiscoveryOutcome::Ignored(reason)
+            } else {
+                products.push(Product {
+                    name: Some(name.clone()),
+                    path: Some(path.clone()),
+                    edition: Some(edition),
+                    ..Product::default()
+                });
+                DiscoveryOutcome::Inferred
+            };
+            report.push(DiscoveryCandidate { kind, name, path, outcome });
+        }
+        Ok((products, report))
+    }
+}
+
+/// Name and path of `dir/*.rs` and `dir/*/main.rs`
+fn discoverable(fs: &dyn AbstractFilesystem, dir: &str) -> Result<Vec<(String, String)>, Error> {
+    let files = match fs.file_names_in(dir) {
+        Ok(files) => files,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut out = Vec::new();
+    for name in files {
+        let path = format!("{dir}/{name}");
+        if let Some(stem) = name.strip_suffix(".rs") {
+            out.push((stem.to_string(), path));
+        } else if fs.file_names_in(&path).is_ok_and(|sub| sub.contains("main.rs")) {
+            out.push((name.to_string(), format!("{path}/main.rs")));
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,71 @@
+//! Formatting-preserving changes to `Cargo.toml` files, using `toml_edit`
+
+use crate::{Error, ManifestEdit};
+use toml_edit::{DocumentMut, Item, Key, TableLike};
+
+/// Applies all the edits, checking that the old values are still there
+pub(crate) fn apply_edits<'a>(toml_source: &str, edits: impl Iterator<Item = &'a ManifestEdit>) -> Result<String, Error> {
+    let mut doc: DocumentMut = toml_source.parse().map_err(|_| Error::Other("can't parse the manifest for editing"))?;
+    for edit in edits {
+        let value = item_mut(doc.as_item_mut(), &edit.toml_path)
+            .and_then(Item::as_value_mut)
+            .filter(|v| v.as_str() == Some(edit.old.as_str()))
+            .ok_or(Error::Other("the manifest has changed since the edits were planned"))?;
+        set_str(value, &edit.new);
+    }
+    Ok(doc.to_string())
+}
+
+/// Follows keys through tables, inline tables, and dotted keys. Numeric keys are indices in arrays (of tables).
+pub(crate) fn item_mut<'d>(mut item: &'d mut Item, path: &[String]) -> Option<&'d mut Item> {
+    for key in path {
+        item = match key.parse::<usize>() {
+            Ok(i) if item.is_array_of_tables() || item.is_array() => item.get_mut(i)?,
+            _ => item.get_mut(key.as_str())?,
+        };
+    }
+    Some(item)
+}
+
+/// Changes the key, keeping its value, position, and the comments around it. Returns `false` if there's no `old` key, or `new` is already used.
+pub(crate) fn rename_key(table: &mut dyn TableLike, old: &str, new: &str) -> bool {
+    if table.contains_key(new) {
+        return false;
+    }
+    let Some(old_key) = table.key(old) else { return false };
+    let new_key = Key::new(new).with_leaf_decor(old_key.leaf_decor().clone()).with_dotted_decor(old_key.dotted_decor().clone());
+
+    // keys can only be appended, so the keys after it have to be moved too
+    let following: Vec<Key> = table.iter().map(|(k, _)| k).skip_while(|&k| k != old).skip(1).filter_map(|k| table.key(k).cloned()).collect();
+    let Some(item) = table.remove(old) else { return false };
+    let following: Vec<(Key, Item)> = following.into_iter().filter_map(|k| Some((table.remove(k.get())?, k))).map(|(item, k)| (k, item)).collect();
+    table.entry_format(&new_key).or_insert(item);
+    for (key, item) in following {
+        table.entry_format(&key).or_insert(item);
+    }
+    true
+}
+
+/// Replaces the value, keeping the whitespace and comments around it
+pub(crate) fn set_str(value: &mut toml_edit::Value, new: &str) {
+    replace_value(value, new.into());
+}
+
+fn replace_value(value: &mut toml_edit::Value, new: toml_edit::Value) {
+    let decor = value.decor().clone();
+    *value = new;
+    *value.decor_mut() = decor;
+}
+
+/// Sets the value at the path, adding the key if it's missing. The tables leading to it must exist.
+pub(crate) fn set_value(root: &mut Item, path: &[String], new: toml_edit::Value) -> Result<(), Error> {
+    let (key, parent) = path.split_last().ok_or(Error::Other("empty path"))?;
+    let table = item_mut(root, parent).and_then(Item::as_table_like_mut).ok_or(Error::Other("the table to edit is missing from the manifest"))?;
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(value) => replace_value(value, new),
+        None => {
+            table.insert(key, Item::Value(new));
+        },
+    }
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use crate::{AbstractFilesystem, DiscoveryOutcome, Edition, Error, Inheritable, Manifest, ProductKind, Resolver};
+use std::fmt;
+
+/// What changes when the edition of a package or a virtual workspace changes. See [`Manifest::edition_migration`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EditionMigration {
+    /// The current edition (a virtual workspace without `workspace.package.edition` is treated as 2015)
+    pub from: Edition,
+    /// The new edition
+    pub to: Edition,
+    /// Everything that needs attention, apart from the `edition` itself
+    pub changes: Vec<EditionChange>,
+}
+
+/// A consequence of changing the edition
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum EditionChange {
+    /// `rust-version` is older than the first Rust release that supports the new edition
+    RustVersion { old: String, new: String },
+    /// `resolver` isn't set, so the package switches to the new edition's default feature resolver
+    ImpliedResolver { old: Resolver, new: Resolver },
+    /// Virtual workspaces don't have an edition, so they use resolver 1 unless `workspace.resolver` is set
+    WorkspaceResolver { resolver: Resolver },
+    /// The product sets its own `edition`, which is different from the new one. It's kept as it is, because it's been pinned deliberately.
+    ProductEdition { kind: ProductKind, name: Option<String>, edition: Edition },
+    /// The file would be autodiscovered as a product, and it isn't now
+    Discovered { kind: ProductKind, path: String },
+    /// The file is autodiscovered as a product now, and it wouldn't be
+    Undiscovered { kind: ProductKind, path: String },
+}
+
+impl fmt::Display for EditionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RustVersion { old, new } => write!(f, "rust-version needs to be at least {new} (it's {old})"),
+            Self::ImpliedResolver { old, new } => write!(f, "the default feature resolver changes from {old} to {new}"),
+            Self::WorkspaceResolver { resolver } => write!(f, "the virtual workspace needs resolver = \"{resolver}\""),
+            Self::ProductEdition { kind, name, edition } => write!(f, "{kind} `{}` has edition {edition}", name.as_deref().unwrap_or_default()),
+            Self::Discovered { kind, path } => write!(f, "`{path}` would be autodiscovered as a {kind} target"),
+            Self::Undiscovered { kind, path } => write!(f, "`{path}` would not be autodiscovered as a {kind} target any more"),
+        }
+    }
+}
+
+impl<Metadata> Manifest<Metadata> {
+    /// Changes needed to switch the package (or `workspace.package` of a virtual workspace) to the given edition.
+    ///
+    /// Use a manifest that hasn't been completed (e.g. from [`Manifest::from_str`]), because completion adds autodiscovered products.
+    /// `fs` is the package's directory, used to find products that would be autodiscovered differently (in the 2015 edition, listing any `[[bin]]` disables discovery of binaries, unless `autobins = true` is set explicitly).
+    ///
+    /// Fails if the package inherits its edition from the workspace. Migrate the workspace's manifest instead.
+    pub fn edition_migration<Fs: AbstractFilesystem>(&self, edition: Edition, fs: Fs) -> Result<EditionMigration, Error> {
+        let mut changes = Vec::new();
+        let ws_template = self.workspace.as_ref().and_then(|ws| ws.package.as_ref());
+        let (from, rust_version) = match &self.package {
+            Some(package) => {
+                let Inheritable::Set(from) = package.edition else {
+                    return Err(Error::Other("edition is inherited from the workspace"));
+                };
+                (from, package.rust_version.as_ref().and_then(|r| r.get().ok()))
+            },
+            None => {
+                let ws = self.workspace.as_ref().ok_or(Error::Other("not a package or a workspace"))?;
+                if ws.resolver.is_none() && edition.default_resolver() != Resolver::V1 {
+                    changes.push(EditionChange::WorkspaceResolver { resolver: edition.default_resolver() });
+                }
+                (ws_template.and_then(|p| p.edition).unwrap_or_default(), ws_template.and_then(|p| p.rust_version.as_ref()))
+            },
+        };
+
+        if let Some(old) = rust_version {
+            let new = edition.min_rust_version_minor();
+            if rust_version_minor(old).is_some_and(|minor| minor < new) {
+                changes.push(EditionChange::RustVersion { old: old.clone(), new: format!("1.{new}") });
+            }
+        }
+
+        if let Some(package) = &self.package {
+            let explicit_resolver = package.resolver.or_else(|| self.workspace.as_ref()?.resolver);
+            if explicit_resolver.is_none() && from.default_resolver() != edition.default_resolver() {
+                changes.push(EditionChange::ImpliedResolver { old: from.default_resolver(), new: edition.default_resolver() });
+            }
+
+            for (kind, p) in self.products_by_kind() {
+                if let Some(e) = p.edition.filter(|&e| e != edition) {
+                    changes.push(EditionChange::ProductEdition { kind, name: p.name.clone(), edition: e });
+                }
+            }
+
+            for kind in [ProductKind::Bin, ProductKind::Example, ProductKind::Test, ProductKind::Bench] {
+                let inferred = |e| -> Result<Vec<_>, Error> {
+                    Ok(self.discover_products(kind, e, &fs)?.1.into_iter().filter(|c| c.outcome == DiscoveryOutcome::Inferred).map(|c| c.path).collect())
+                };
+                let (before, after) = (inferred(from)?, inferred(edition)?);
+                changes.extend(after.iter().filter(|p| !before.contains(p)).map(|path| EditionChange::Discovered { kind, path: path.clone() }));
+                changes.extend(before.into_iter().filter(|p| !after.contains(p)).map(|path| EditionChange::Undiscovered { kind, path }));
+            }
+        }
+
+        changes.sort_unstable();
+        Ok(EditionMigration { from, to: edition, changes })
+    }
+}
+
+impl EditionMigration {
+    /// Sets the new edition, and makes the changes that keep the manifest working the same way:
+    /// raises `rust-version`, sets `workspace.resolver` of a virtual workspace,
+    /// and sets `autobins`, etc. explicitly, so that the same products are discovered.
+    ///
+    /// The implied resolver change is not prevented. Products that set their own `edition` keep it.
+    pub fn apply<M>(&self, manifest: &mut Manifest<M>) {
+        if let Some(package) = &mut manifest.package {
+            package.edition = Inheritable::Set(self.to);
+        } else if let Some(p) = manifest.workspace.as_mut().and_then(|ws| ws.package.as_mut()).filter(|p| p.edition.is_some()) {
+            p.edition = Some(self.to);
+        }
+
+        for change in &self.changes {
+            match change {
+                EditionChange::RustVersion { new, .. } => match &mut manifest.package {
+                    Some(package) => package.set_rust_version(Some(new.clone())),
+                    None => if let Some(p) = manifest.workspace.as_mut().and_then(|ws| ws.package.as_mut()) {
+                        p.rust_version = Some(new.clone());
+                    },
+                },
+                EditionChange::WorkspaceResolver { resolver } => if let Some(ws) = &mut manifest.workspace {
+                    ws.resolver = Some(*resolver);
+                },
+                EditionChange::Discovered { kind, .. } | EditionChange::Undiscovered { kind, .. } => if let Some(package) = &mut manifest.package {
+                    let discover = matches!(change, EditionChange::Undiscovered { .. });
+                    match kind {
+                        ProductKind::Bin => package.autobins = discover,
+                        ProductKind::Example => package.autoexamples = discover,
+                        ProductKind::Test => package.autotests = discover,
+                        ProductKind::Bench => package.autobenches = discover,
+                        ProductKind::Lib | ProductKind::BuildScript => {},
+                    }
+                },
+                EditionChange::ImpliedResolver { .. } | EditionChange::ProductEdition { .. } => {},
+            }
+        }
+    }
+
+    /// Like [`EditionMigration::apply`], but changes the TOML source of the manifest, preserving formatting and comments
+    #[cfg(feature = "edit")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "edit")))]
+    pub fn apply_to_toml(&self, toml_source: &str) -> Result<String, Error> {
+        use toml_edit::DocumentMut;
+
+        let mut doc: DocumentMut = toml_source.parse().map_err(|_| Error::Other("can't parse the manifest for editing"))?;
+        let is_package = doc.contains_key("package");
+        let package_path = |key: &str| -> Vec<String> {
+            if is_package { vec!["package".into(), key.into()] } else { vec!["workspace".into(), "package".into(), key.into()] }
+        };
+        let mut edits: Vec<(Vec<String>, toml_edit::Value)> = Vec::new();
+
+        let edition = package_path("edition");
+        if is_package || crate::edit::item_mut(doc.as_item_mut(), &edition).is_some_and(|e| e.is_value()) {
+            edits.push((edition, self.to.to_string().into()));
+        }
+        for change in &self.changes {
+            match change {
+                EditionChange::RustVersion { new, .. } => edits.push((package_path("rust-version"), new.as_str().into())),
+                EditionChange::WorkspaceResolver { resolver } => edits.push((vec!["workspace".into(), "resolver".into()], resolver.to_string().into())),
+                EditionChange::Discovered { kind, .. } | EditionChange::Undiscovered { kind, .. } => {
+                    let key = match kind {
+                        ProductKind::Bin => "autobins",
+                        ProductKind::Example => "autoexamples",
+                        ProductKind::Test => "autotests",
+                        ProductKind::Bench => "autobenches",
+                        ProductKind::Lib | ProductKind::BuildScript => continue,
+                    };
+                    edits.push((vec!["package".into(), key.into()], matches!(change, EditionChange::Undiscovered { .. }).into()));
+                },
+                EditionChange::ImpliedResolver { .. } | EditionChange::ProductEdition { .. } => {},
+            }
+        }
+        for (path, value) in edits {
+            crate::edit::set_value(doc.as_item_mut(), &path, value)?;
+        }
+        Ok(doc.to_string())
+    }
+}
+
+/// Minor part of versions like `1.56` or `1.56.1`
+fn rust_version_minor(version: &str) -> Option<u16> {
+    version.trim().strip_prefix("1.")?.split('.').next()?.parse().ok()
+}
+
+#[test]
+fn rust_versions() {
+    assert_eq!(rust_version_minor("1.56"), Some(56));
+    assert_eq!(rust_version_minor(" 1.85.1"), Some(85));
+    assert_eq!(rust_version_minor("2.0"), None);
+}
@@ -16,6 +16,8 @@ pub enum Error {
     InheritedUnknownValue,
     /// Manifest uses workspace inheritance, but the root workspace is missing data
     WorkspaceIntegrity(String),
+    /// A workspace member failed to load. The path is of the member's directory.
+    WorkspaceMember(Box<(Error, PathBuf)>),
     /// ???
     Other(&'static str),
 }
@@ -26,6 +28,7 @@ impl StdErr for Error {
             Error::Parse(err) => Some(err),
             Error::Io(err) => Some(err),
             Error::Workspace(err) => Some(&err.0),
+            Error::WorkspaceMember(err) => Some(&err.0),
             Error::Other(_) | Error::InheritedUnknownValue | Error::WorkspaceIntegrity(_) => None,
         }
     }
@@ -46,6 +49,7 @@ impl fmt::Display for Error {
                 f.write_str(": ")?;
                 err_path.0.fmt(f)
             }
+            Error::WorkspaceMember(err_path) => write!(f, "can't load workspace member at {}: {}", err_path.1.display(), err_path.0),
             Error::InheritedUnknownValue => f.write_str("value from workspace hasn't been set"),
         }
     }
@@ -59,6 +63,7 @@ impl Clone for Error {
             Error::Other(msg) => Error::Other(msg),
             Error::WorkspaceIntegrity(msg) => Error::WorkspaceIntegrity(msg.clone()),
             Error::Workspace(e) => Error::Workspace(e.clone()),
+            Error::WorkspaceMember(e) => Error::WorkspaceMember(e.clone()),
             Error::InheritedUnknownValue => Error::InheritedUnknownValue,
         }
     }
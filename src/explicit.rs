@@ -0,0 +1,285 @@
+use crate::strict::{MANIFEST_KEYS, PACKAGE_KEYS, PRODUCT_KEYS, PROFILE_KEYS, TARGET_KEYS, WORKSPACE_KEYS};
+use crate::{Error, Manifest, Package, Product, Profile, Target, Value, Workspace};
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Keys that have been written in `Cargo.toml`, as opposed to fields filled in with default values.
+///
+/// It's set when parsing the manifest, and isn't updated when the fields are modified.
+/// It's not a part of the manifest's data, so it's ignored by `PartialEq`.
+#[derive(Debug, Clone, Default)]
+pub struct ExplicitKeys {
+    /// Values are kept only if they're small enough to be a default value
+    keys: BTreeMap<String, Option<Value>>,
+}
+
+impl ExplicitKeys {
+    /// The key was in the table, spelled as in `Cargo.toml`, e.g. `"default-run"`
+    #[must_use]
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.contains_key(key)
+    }
+
+    /// All keys written in the table
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.keys().map(String::as_str)
+    }
+
+    /// Not parsed from `Cargo.toml`, or the table was empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub(crate) fn from_table(table: &toml::Table) -> Self {
+        Self {
+            keys: table.iter().map(|(k, v)| {
+                let small = match v {
+                    Value::Array(a) => a.is_empty(),
+                    Value::Table(t) => t.is_empty(),
+                    _ => true,
+                };
+                (k.clone(), small.then(|| v.clone()))
+            }).collect(),
+        }
+    }
+}
+
+impl PartialEq for ExplicitKeys {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<Metadata> Manifest<Metadata> {
+    /// Sets `explicit` of the manifest and its sections from the raw TOML it's been parsed from
+    pub(crate) fn set_explicit_keys(&mut self, raw: &toml::Table) {
+        let section = |key: &str| raw.get(key).and_then(Value::as_table);
+        self.explicit = ExplicitKeys::from_table(raw);
+        if let Some((package, t)) = self.package.as_mut().zip(section("package")) {
+            package.explicit = ExplicitKeys::from_table(t);
+        }
+        if let Some((ws, t)) = self.workspace.as_mut().zip(section("workspace")) {
+            ws.explicit = ExplicitKeys::from_table(t);
+        }
+        if let Some((lib, t)) = self.lib.as_mut().zip(section("lib")) {
+            lib.explicit = ExplicitKeys::from_table(t);
+        }
+        for (key, products) in [("bin", &mut self.bin), ("example", &mut self.example), ("test", &mut self.test), ("bench", &mut self.bench)] {
+            // products haven't been sorted yet
+            let tables = raw.get(key).and_then(Value::as_array).into_iter().flatten().map(Value::as_table);
+            for (product, t) in products.iter_mut().zip(tables) {
+                product.explicit = t.map(ExplicitKeys::from_table).unwrap_or_default();
+            }
+        }
+        for (name, t) in section("profile").into_iter().flatten() {
+            if let Some((profile, t)) = self.profile.get_mut(name).zip(t.as_table()) {
+                profile.explicit = ExplicitKeys::from_table(t);
+            }
+        }
+        for (cfg, t) in section("target").into_iter().flatten() {
+            if let Some((target, t)) = self.target.get_mut(cfg).zip(t.as_table()) {
+                target.explicit = ExplicitKeys::from_table(t);
+            }
+        }
+    }
+}
+
+impl<Metadata: Serialize> Manifest<Metadata> {
+    /// Serialize to `Cargo.toml` syntax, keeping default values only where they were written explicitly.
+    ///
+    /// Unlike serializing with `toml::to_string`, this omits defaults filled in when parsing (like `version = "0.0.0"` or `edition = "2015"`),
+    /// and keeps explicit default values (like `autobins = true`), according to the `explicit` fields.
+    pub fn to_string_as_written(&self) -> Result<String, Error> {
+        let err = |_| Error::Other("can't serialize manifest");
+        let mut out = match Value::try_from(self).map_err(err)? {
+            Value::Table(t) => t,
+            _ => return Err(Error::Other("can't serialize manifest")),
+        };
+        as_written::<Manifest>(&mut out, &self.explicit);
+
+        if let Some((package, t)) = self.package.as_ref().zip(section(&mut out, "package")) {
+            as_written::<Package>(t, &package.explicit);
+        }
+        if let Some((ws, t)) = self.workspace.as_ref().zip(section(&mut out, "workspace")) {
+            as_written::<Workspace>(t, &ws.explicit);
+        }
+        if let Some((lib, t)) = self.lib.as_ref().zip(section(&mut out, "lib")) {
+            as_written::<Product>(t, &lib.explicit);
+        }
+        if let Some(t) = section(&mut out, "profile") {
+            for (name, t) in t.iter_mut() {
+                if let Some((profile, t)) = self.profile.get(name).zip(t.as_table_mut()) {
+                    as_written::<Profile>(t, &profile.explicit);
+                }
+            }
+        }
+        if let Some(t) = section(&mut out, "target") {
+            for (cfg, t) in t.iter_mut() {
+                if let Some((target, t)) = self.target.get(cfg).zip(t.as_table_mut()) {
+                    as_written::<Target>(t, &target.explicit);
+                }
+            }
+        }
+        for (key, products) in [("bin", &self.bin), ("example", &self.example), ("test", &self.test), ("bench", &self.bench)] {
+            let tables = out.get_mut(key).and_then(Value::as_array_mut).into_iter().flatten().map(Value::as_table_mut);
+            for (product, t) in products.iter().zip(tables) {
+                if let Some(t) = t {
+                    as_written::<Product>(t, &product.explicit);
+                }
+            }
+        }
+
+        toml::to_string(&Ordered(&Value::Table(out), Section::Manifest)).map_err(err)
+    }
+}
+
+fn section<'t>(out: &'t mut toml::Table, key: &str) -> Option<&'t mut toml::Table> {
+    out.get_mut(key).and_then(Value::as_table_mut)
+}
+
+/// Parsed the same way as in `Manifest::from_str`
+trait FromRaw: Serialize + DeserializeOwned {
+    fn from_raw(table: toml::Table) -> Option<Self>;
+}
+
+impl FromRaw for Manifest {
+    fn from_raw(table: toml::Table) -> Option<Self> {
+        Self::from_table(table).ok()
+    }
+}
+
+impl FromRaw for Package {
+    fn from_raw(table: toml::Table) -> Option<Self> {
+        let mut package = Self::deserialize(Value::Table(table.clone())).ok()?;
+        package.explicit = ExplicitKeys::from_table(&table);
+        package.set_publish_default();
+        Some(package)
+    }
+}
+
+macro_rules! from_raw {
+    ($($t:ty),*) => {$(
+        impl FromRaw for $t {
+            fn from_raw(table: toml::Table) -> Option<Self> {
+                let explicit = ExplicitKeys::from_table(&table);
+                let mut parsed = Self::deserialize(Value::Table(table)).ok()?;
+                parsed.explicit = explicit;
+                Some(parsed)
+            }
+        }
+    )*};
+}
+from_raw!(Workspace, Product, Profile, Target);
+
+/// Removes keys that weren't explicit, if they'd be set to the same value anyway,
+/// and adds explicit keys that have been skipped, because they have a default value.
+fn as_written<T: FromRaw>(out: &mut toml::Table, explicit: &ExplicitKeys) {
+    let reserialized = |table: toml::Table| -> Option<toml::Table> {
+        match Value::try_from(T::from_raw(table)?).ok()? {
+            Value::Table(t) => Some(t),
+            _ => None,
+        }
+    };
+
+    let mut kept: toml::Table = out.iter().filter(|(k, _)| explicit.contains(k)).map(|(k, v)| (k.clone(), v.clone())).collect();
+    if reserialized(kept.clone()).is_none() {
+        // not parsed from `Cargo.toml`, so required keys aren't in `explicit`
+        let required: Vec<_> = out.iter().filter(|&(k, _)| !kept.contains_key(k) && {
+            let mut without = out.clone();
+            without.remove(k);
+            reserialized(without).is_none()
+        }).map(|(k, v)| (k.clone(), v.clone())).collect();
+        kept.extend(required);
+    }
+    // defaults can depend on each other, e.g. `publish` on `version`, so keys are kept until the defaults stop changing
+    loop {
+        let Some(defaults) = reserialized(kept.clone()) else { return };
+        let changed: Vec<_> = out.iter().filter(|&(k, v)| !kept.contains_key(k) && defaults.get(k) != Some(v)).map(|(k, v)| (k.clone(), v.clone())).collect();
+        if changed.is_empty() {
+            break;
+        }
+        kept.extend(changed);
+    }
+    out.retain(|k, _| kept.contains_key(k));
+
+    let skipped: Vec<_> = explicit.keys.iter().filter_map(|(k, v)| Some((k, v.as_ref()?))).filter(|&(k, _)| !out.contains_key(k)).collect();
+    if skipped.is_empty() {
+        return;
+    }
+    let mut with = out.clone();
+    with.extend(skipped.iter().map(|&(k, v)| (k.clone(), v.clone())));
+    if let Some(t) = reserialized(with) {
+        for (key, value) in skipped {
+            if !t.contains_key(key) {
+                out.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Serializes known keys in the same order as the structs do
+struct Ordered<'a>(&'a Value, Section);
+
+#[derive(Clone, Copy)]
+enum Section {
+    Manifest,
+    Package,
+    Workspace,
+    Profiles,
+    Profile,
+    Targets,
+    Target,
+    Product,
+    Other,
+}
+
+impl Section {
+    fn order(self) -> &'static [&'static str] {
+        match self {
+            Self::Manifest => MANIFEST_KEYS,
+            Self::Package => PACKAGE_KEYS,
+            Self::Workspace => WORKSPACE_KEYS,
+            Self::Profile => PROFILE_KEYS,
+            Self::Target => TARGET_KEYS,
+            Self::Product => PRODUCT_KEYS,
+            Self::Profiles | Self::Targets | Self::Other => &[],
+        }
+    }
+
+    fn nested(self, key: &str) -> Self {
+        match (self, key) {
+            (Self::Manifest, "package") => Self::Package,
+            (Self::Manifest, "workspace") => Self::Workspace,
+            (Self::Manifest, "profile") => Self::Profiles,
+            (Self::Manifest, "target") => Self::Targets,
+            (Self::Manifest, "lib" | "bin" | "example" | "test" | "bench") => Self::Product,
+            (Self::Profiles, _) => Self::Profile,
+            (Self::Targets, _) => Self::Target,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl Serialize for Ordered<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Self(value, section) = *self;
+        match value {
+            Value::Table(table) => {
+                let order = section.order();
+                let mut keys: Vec<&String> = table.keys().collect();
+                keys.sort_by_key(|k| order.iter().position(|o| o == k).unwrap_or(order.len()));
+                let mut map = serializer.serialize_map(Some(keys.len()))?;
+                for key in keys {
+                    map.serialize_entry(key, &Ordered(&table[key], section.nested(key)))?;
+                }
+                map.end()
+            },
+            // arrays of tables are in the section of their key
+            Value::Array(items) => serializer.collect_seq(items.iter().map(|v| Ordered(v, section))),
+            other => other.serialize(serializer),
+        }
+    }
+}
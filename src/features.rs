@@ -1,12 +1,20 @@
 //! Helper for parsing the microsyntax of the `[features]` section and computing implied features from optional dependencies.
 
 use crate::{Dependency, DepsSet, Manifest, Product, TargetDepsSet};
+pub use crate::Kind;
 use std::borrow::Cow;
 use std::collections::hash_map::{Entry, RandomState};
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::hash::BuildHasher;
 use std::marker::PhantomData;
 
+mod graph;
+mod lint;
+mod matrix;
+pub use self::graph::*;
+pub use self::lint::*;
+pub use self::matrix::*;
+
 /// Maximum number of features and dependencies, to protect against DoS
 /// crates.io limit is 300.
 const MAX_ITEMS: usize = 2048;
@@ -42,6 +50,9 @@ pub struct Features<'manifest, 'deps, Hasher = RandomState> {
 
     /// A redirect from removed feature to its replacements
     pub hidden_features: HashMap<&'manifest str, BTreeSet<&'manifest str>, Hasher>,
+
+    /// Features changed or removed by removal of the hidden ones, as they were before
+    before_collapse: HashMap<&'manifest str, Feature<'manifest>, Hasher>,
 }
 
 /// How an enabled feature affects the dependency
@@ -87,6 +98,9 @@ pub struct Feature<'a> {
     pub explicit: bool,
 }
 
+type FeaturesByKey<'a, S> = HashMap<&'a str, Feature<'a>, S>;
+type HiddenFeatures<'a, S> = HashMap<&'a str, BTreeSet<&'a str>, S>;
+
 /// Outer key is the dependency key/name, the `Vec` contains feature names
 pub type DependenciesEnabledByFeatures<'a, S> = HashMap<&'a str, Vec<(&'a str, &'a DepAction<'a>)>, S>;
 
@@ -218,13 +232,14 @@ impl<'manifest, 'config, RandomState: BuildHasher + Default> Resolver<'config, R
 
         Self::remove_redundant_dep_action_features(&mut features, &dependencies);
         Self::set_enabled_by(&mut features);
-        let hidden_features = self.remove_hidden_features(&mut features);
+        let (hidden_features, before_collapse) = self.remove_hidden_features(&mut features);
 
         Features {
             features,
             dependencies,
             removed_hidden_features: !hidden_features.is_empty(),
             hidden_features,
+            before_collapse,
         }
     }
 
@@ -247,19 +262,22 @@ impl<'manifest, 'config, RandomState: BuildHasher + Default> Resolver<'config, R
 
         Self::remove_redundant_dep_action_features(&mut features, &dependencies);
         Self::set_enabled_by(&mut features);
-        let hidden_features = self.remove_hidden_features(&mut features);
+        let (hidden_features, before_collapse) = self.remove_hidden_features(&mut features);
 
         Features {
             features,
             dependencies,
             removed_hidden_features: !hidden_features.is_empty(),
             hidden_features,
+            before_collapse,
         }
     }
 }
 
 /// For parsing in `parse_custom`. Can be constructed from the crates.io index, instead of `Cargo.toml`.
 ///
+/// With the `index` feature enabled, `index::IndexEntry::features_input` does this for you.
+///
 /// Note about lifetimes: it's not possible to make `&Dependency` on the fly.
 /// You will have to collect *owned* `Dependency` objects to a `Vec` or `HashMap` first.
 #[derive(Debug, Clone)]
@@ -273,14 +291,6 @@ pub struct ParseDependency<'a, 'tmp> {
     pub dep: &'tmp Dependency,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
-pub enum Kind {
-    #[default]
-    Normal,
-    Build,
-    Dev,
-}
-
 impl<'a, 'c, S: BuildHasher + Default> Resolver<'c, S> {
     fn parse_features(features: impl Iterator<Item = (&'a String, &'a Vec<String>)>, has_explicit_default: bool) -> HashMap<&'a str, Feature<'a>, S> {
         features
@@ -458,21 +468,21 @@ impl<'a, 'c, S: BuildHasher + Default> Resolver<'c, S> {
 
     /// find `__features` and inline them
     #[inline(never)]
-    fn remove_hidden_features(&self, features: &mut HashMap<&'a str, Feature<'a>, S>) -> HashMap<&'a str, BTreeSet<&'a str>, S> {
+    fn remove_hidden_features(&self, features: &mut FeaturesByKey<'a, S>) -> (HiddenFeatures<'a, S>, FeaturesByKey<'a, S>) {
         let features_to_remove: BTreeSet<_> = features.keys().copied().filter(|&k| {
             k.starts_with('_') && !self.always_keep.is_some_and(|cb| (cb)(k)) // if user thinks that is useful info
         }).collect();
 
-        let mut removed_mapping = HashMap::<_, _, S>::default();
-
-        if features_to_remove.is_empty() {
-            return removed_mapping;
-        }
+        let mut removed_mapping: HiddenFeatures<'a, S> = HashMap::default();
+        // only the features that are touched are copied, for `graph()`
+        let mut before_collapse: FeaturesByKey<'a, S> = HashMap::default();
 
         features_to_remove.into_iter().for_each(|key| {
             let Some(mut janky) = features.remove(key) else { return };
+            before_collapse.entry(key).or_insert_with(|| janky.clone());
 
             janky.enabled_by.iter().for_each(|&parent_key| if let Some(parent) = features.get_mut(parent_key) {
+                before_collapse.entry(parent_key).or_insert_with(|| parent.clone());
                 parent.enabled_by.remove(janky.key); // just in case it's circular
 
                 // the filter tries to avoid adding new redundant enables_features, but it's order-dependent
@@ -492,6 +502,7 @@ impl<'a, 'c, S: BuildHasher + Default> Resolver<'c, S> {
 
             janky.enables_features.iter().for_each(|&f| {
                 if let Some(child) = features.get_mut(f) {
+                    before_collapse.entry(f).or_insert_with(|| child.clone());
                     // this list is sometimes a bit redundant,
                     // but the hidden feature cleanup is not recursive, so it needs to contain all possible places
                     child.enabled_by.extend(&janky.enabled_by);
@@ -506,15 +517,16 @@ impl<'a, 'c, S: BuildHasher + Default> Resolver<'c, S> {
             });
 
             janky.enables_deps.iter().filter(|&(k, a)| !a.is_dep_only && !janky.enables_features.contains(k)).for_each(|(&d, _)| {
-                if let Some(d) = features.get_mut(d) {
-                    d.enabled_by.extend(&janky.enabled_by);
-                    d.enabled_by.remove(janky.key);
+                if let Some(dep_feature) = features.get_mut(d) {
+                    before_collapse.entry(d).or_insert_with(|| dep_feature.clone());
+                    dep_feature.enabled_by.extend(&janky.enabled_by);
+                    dep_feature.enabled_by.remove(janky.key);
                 }
             });
 
             removed_mapping.entry(janky.key).or_default().append(&mut janky.enables_features);
         });
-        removed_mapping
+        (removed_mapping, before_collapse)
     }
 }
 
@@ -0,0 +1,357 @@
+use super::{Feature, Features, Kind};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::hash::BuildHasher;
+
+/// What to include in the [`FeatureGraph`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct GraphOptions {
+    /// Skip features that aren't [user-facing](super::Feature::is_user_facing).
+    ///
+    /// Edges to implicit features of optional dependencies are redirected to the dependencies.
+    pub hide_non_user_facing: bool,
+    /// If `false`, features removed by the resolver (see [`Features::hidden_features`]) are added back as separate nodes,
+    /// with the edges they had in `[features]`. If `true`, they stay inlined into the features that enabled them.
+    pub collapse_hidden_features: bool,
+    /// Color optional dependencies differently depending on whether they're normal, build, or dev dependencies.
+    pub color_deps_by_kind: bool,
+}
+
+impl Default for GraphOptions {
+    fn default() -> Self {
+        Self {
+            hide_non_user_facing: false,
+            collapse_hidden_features: true,
+            color_deps_by_kind: true,
+        }
+    }
+}
+
+/// Graph of features and dependencies they enable. Make it with [`Features::graph`].
+///
+/// It can be rendered with [`FeatureGraph::to_dot`] and [`FeatureGraph::to_mermaid`],
+/// or serialized with Serde (e.g. to JSON). The serialized form is versioned by the `schema` field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct FeatureGraph<'a> {
+    /// Version of the serialized structure, currently `1`
+    pub schema: u32,
+    /// Sorted features first, then dependencies
+    pub nodes: Vec<GraphNode<'a>>,
+    /// Sorted by source and target
+    pub edges: Vec<GraphEdge<'a>>,
+    #[serde(skip)]
+    color_deps_by_kind: bool,
+}
+
+/// A feature or a dependency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct GraphNode<'a> {
+    /// Unique, `feature:name` or `dep:key`
+    pub id: String,
+    /// Feature name or dependency key
+    pub name: &'a str,
+    #[serde(rename = "type")]
+    pub node_type: NodeType,
+    /// For features: it's user-facing. For dependencies: it's optional.
+    pub public: bool,
+    /// For dependencies: the crate name, if it's different from the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crate_name: Option<&'a str>,
+    /// For dependencies: the most common kind of this dependency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dep_kind: Option<Kind>,
+}
+
+/// Type of a [`GraphNode`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    /// From `[features]`
+    Feature,
+    /// Feature implied by an optional dependency
+    ImplicitFeature,
+    /// Feature that has been inlined by the resolver
+    HiddenFeature,
+    /// From `[dependencies]`
+    Dependency,
+}
+
+/// How one node enables another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct GraphEdge<'a> {
+    /// Id of the enabling feature
+    pub from: String,
+    /// Id of the enabled feature or dependency
+    pub to: String,
+    /// Uses `dep?/feature`, so it doesn't enable the dependency by itself
+    pub conditional: bool,
+    /// Features of the dependency that are enabled
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dep_features: Vec<Cow<'a, str>>,
+}
+
+impl<'manifest, 'deps, S: BuildHasher> Features<'manifest, 'deps, S> {
+    /// Make a graph of the features, which can be exported to DOT, Mermaid or JSON.
+    #[must_use]
+    pub fn graph(&self, options: &GraphOptions) -> FeatureGraph<'manifest> where 'deps: 'manifest {
+        let before_collapse = (!options.collapse_hidden_features).then_some(&self.before_collapse);
+        let features: BTreeMap<&str, &Feature<'manifest>> = self.features.iter().chain(before_collapse.into_iter().flatten()).map(|(&k, f)| (k, f)).collect();
+        let is_hidden = |key: &str| self.hidden_features.contains_key(key);
+        let is_shown = |key: &str| {
+            features.get(key).is_some_and(|f| !options.hide_non_user_facing || f.is_user_facing() || is_hidden(key))
+        };
+
+        let mut nodes = BTreeMap::new();
+        let mut dep_nodes = BTreeMap::new();
+        let mut edges = BTreeMap::new();
+        let mut add_dep_edge = |edges: &mut BTreeMap<_, GraphEdge<'manifest>>, from: String, dep_key: &'manifest str, conditional: bool, dep_features: Vec<Cow<'manifest, str>>| {
+            if let Some(dep) = self.dependencies.get(dep_key) {
+                dep_nodes.entry(dep_key).or_insert_with(|| {
+                    let (d, kind) = dep.detail();
+                    GraphNode {
+                        id: format!("dep:{dep_key}"),
+                        name: dep_key,
+                        node_type: NodeType::Dependency,
+                        public: d.optional(),
+                        crate_name: Some(dep.crate_name).filter(|&c| c != dep_key),
+                        dep_kind: Some(kind),
+                    }
+                });
+            }
+            let to = format!("dep:{dep_key}");
+            let e = edges.entry((from.clone(), to.clone())).or_insert(GraphEdge { from, to, conditional, dep_features: vec![] });
+            e.conditional &= conditional;
+            e.dep_features.extend(dep_features);
+        };
+
+        for (&key, &f) in &features {
+            if !is_shown(key) {
+                continue;
+            }
+            let id = format!("feature:{key}");
+            nodes.insert(key, GraphNode {
+                id: id.clone(),
+                name: key,
+                node_type: if is_hidden(key) { NodeType::HiddenFeature } else if f.explicit { NodeType::Feature } else { NodeType::ImplicitFeature },
+                public: f.is_user_facing(),
+                crate_name: None,
+                dep_kind: None,
+            });
+            for &target in &f.enables_features {
+                if is_shown(target) {
+                    let to = format!("feature:{target}");
+                    edges.insert((id.clone(), to.clone()), GraphEdge { from: id.clone(), to, conditional: false, dep_features: vec![] });
+                } else if let Some(implicit) = features.get(target).filter(|t| !t.explicit) {
+                    // bypass the hidden implicit feature
+                    for (&dep_key, action) in &implicit.enables_deps {
+                        add_dep_edge(&mut edges, id.clone(), dep_key, action.is_conditional, vec![]);
+                    }
+                }
+            }
+            for (&dep_key, action) in &f.enables_deps {
+                let dep_features = action.dep_features.iter().cloned().collect();
+                add_dep_edge(&mut edges, id.clone(), dep_key, action.is_conditional, dep_features);
+            }
+        }
+
+        let nodes: Vec<_> = nodes.into_values().chain(dep_nodes.into_values()).collect();
+        let known: BTreeSet<_> = nodes.iter().map(|n| n.id.clone()).collect();
+        FeatureGraph {
+            schema: 1,
+            nodes,
+            edges: edges.into_values()
+                .filter(|e| known.contains(&e.to))
+                .map(|mut e| { e.dep_features.sort_unstable(); e.dep_features.dedup(); e })
+                .collect(),
+            color_deps_by_kind: options.color_deps_by_kind,
+        }
+    }
+}
+
+impl FeatureGraph<'_> {
+    /// Render as a Graphviz `digraph`
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph features {\n    rankdir=LR;\n    node [shape=box];\n");
+        for n in &self.nodes {
+            let _ = write!(out, "    {} [label={}", dot_quote(&n.id), dot_quote(n.crate_name.map_or_else(|| n.name.to_string(), |c| format!("{} ({c})", n.name)).as_str()));
+            match n.node_type {
+                NodeType::Feature => {},
+                NodeType::ImplicitFeature => out.push_str(", style=dotted"),
+                NodeType::HiddenFeature => out.push_str(", style=dashed, fontcolor=gray"),
+                NodeType::Dependency => {
+                    out.push_str(", shape=ellipse");
+                    if let Some(color) = n.dep_color().filter(|_| self.color_deps_by_kind) {
+                        let _ = write!(out, ", style=filled, fillcolor=\"{color}\"");
+                    }
+                },
+            }
+            if n.name == "default" && n.node_type == NodeType::Feature {
+                out.push_str(", penwidth=2");
+            }
+            out.push_str("];\n");
+        }
+        for e in &self.edges {
+            let _ = write!(out, "    {} -> {}", dot_quote(&e.from), dot_quote(&e.to));
+            let mut attrs = Vec::new();
+            if !e.dep_features.is_empty() {
+                attrs.push(format!("label={}", dot_quote(&e.dep_features.join(", "))));
+            }
+            if e.conditional {
+                attrs.push("style=dashed".into());
+            }
+            if !attrs.is_empty() {
+                let _ = write!(out, " [{}]", attrs.join(", "));
+            }
+            out.push_str(";\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid flowchart
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let ids: BTreeMap<_, _> = self.nodes.iter().enumerate().map(|(i, n)| (n.id.as_str(), format!("n{i}"))).collect();
+        let mut out = String::from("flowchart LR\n");
+        for n in &self.nodes {
+            let id = &ids[n.id.as_str()];
+            let label = mermaid_escape(n.name);
+            let _ = match n.node_type {
+                NodeType::Feature | NodeType::HiddenFeature => writeln!(out, "    {id}[\"{label}\"]"),
+                NodeType::ImplicitFeature => writeln!(out, "    {id}[/\"{label}\"/]"),
+                NodeType::Dependency => writeln!(out, "    {id}([\"{label}\"])"),
+            };
+        }
+        for e in &self.edges {
+            let (Some(from), Some(to)) = (ids.get(e.from.as_str()), ids.get(e.to.as_str())) else { continue };
+            let arrow = if e.conditional { "-.->" } else { "-->" };
+            if e.dep_features.is_empty() {
+                let _ = writeln!(out, "    {from} {arrow} {to}");
+            } else {
+                let _ = writeln!(out, "    {from} {arrow}|\"{}\"| {to}", mermaid_escape(&e.dep_features.join(", ")));
+            }
+        }
+        let mut classes: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for n in &self.nodes {
+            let class = match n.node_type {
+                NodeType::HiddenFeature => "hidden",
+                NodeType::Dependency if !self.color_deps_by_kind => continue,
+                NodeType::Dependency => match n.dep_kind.filter(|_| n.public) {
+                    Some(Kind::Normal) => "normal",
+                    Some(Kind::Build) => "build",
+                    Some(Kind::Dev) => "dev",
+                    None => continue,
+                },
+                _ => continue,
+            };
+            classes.entry(class).or_default().push(&ids[n.id.as_str()]);
+        }
+        for (class, members) in classes {
+            let style = match class {
+                "hidden" => "stroke-dasharray: 5 5,color:gray".to_string(),
+                kind => format!("fill:{}", kind_color(kind)),
+            };
+            let _ = writeln!(out, "    classDef {class} {style}");
+            let _ = writeln!(out, "    class {} {class}", members.join(","));
+        }
+        out
+    }
+}
+
+impl GraphNode<'_> {
+    /// Only optional dependencies are colored
+    fn dep_color(&self) -> Option<&'static str> {
+        if !self.public {
+            return None;
+        }
+        Some(kind_color(match self.dep_kind? {
+            Kind::Normal => "normal",
+            Kind::Build => "build",
+            Kind::Dev => "dev",
+        }))
+    }
+}
+
+fn kind_color(kind: &str) -> &'static str {
+    match kind {
+        "build" => "#f28e2b",
+        "dev" => "#59a14f",
+        _ => "#a0cbe8",
+    }
+}
+
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn mermaid_escape(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
+#[test]
+fn graph_export() {
+    let m = crate::Manifest::from_str(r#"
+[package]
+name = "foo"
+
+[dependencies]
+serde = { version = "1", optional = true }
+plain = "1"
+renamed = { version = "1", package = "real", optional = true }
+
+[build-dependencies]
+cc = { version = "1", optional = true }
+
+[features]
+default = ["std"]
+std = ["serde?/std", "plain/std", "cc"]
+derive = ["serde/derive", "_private"]
+_private = ["dep:renamed", "alloc"]
+alloc = []
+"#).unwrap();
+    let f = super::Resolver::new().parse(&m);
+
+    let g = f.graph(&GraphOptions::default());
+    let names: Vec<_> = g.nodes.iter().map(|n| (n.name, n.node_type)).collect();
+    assert_eq!(names, [
+        ("alloc", NodeType::Feature), ("cc", NodeType::ImplicitFeature), ("default", NodeType::Feature), ("derive", NodeType::Feature),
+        ("serde", NodeType::ImplicitFeature), ("std", NodeType::Feature),
+        ("cc", NodeType::Dependency), ("plain", NodeType::Dependency), ("renamed", NodeType::Dependency), ("serde", NodeType::Dependency),
+    ]);
+    let std_serde = g.edges.iter().find(|e| e.from == "feature:std" && e.to == "dep:serde").unwrap();
+    assert!(std_serde.conditional);
+    assert_eq!(std_serde.dep_features, ["std"]);
+    assert!(g.edges.iter().any(|e| e.from == "feature:derive" && e.to == "dep:renamed"));
+
+    let dot = g.to_dot();
+    assert!(dot.starts_with("digraph features {"));
+    assert!(dot.contains(r#""feature:std" -> "dep:serde" [label="std", style=dashed];"#), "{dot}");
+    assert!(dot.contains(r##""dep:cc" [label="cc", shape=ellipse, style=filled, fillcolor="#f28e2b"];"##), "{dot}");
+    assert!(dot.contains(r#"label="renamed (real)""#));
+
+    let mermaid = g.to_mermaid();
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert!(mermaid.contains(r#"-.->|"std"|"#), "{mermaid}");
+    assert!(mermaid.contains("classDef build fill:#f28e2b"), "{mermaid}");
+
+    let g = f.graph(&GraphOptions { hide_non_user_facing: true, collapse_hidden_features: false, color_deps_by_kind: false });
+    assert!(!g.nodes.iter().any(|n| n.node_type == NodeType::ImplicitFeature && n.name == "cc"));
+    assert!(g.edges.iter().any(|e| e.from == "feature:std" && e.to == "dep:cc"));
+    assert!(g.nodes.iter().any(|n| n.name == "_private" && n.node_type == NodeType::HiddenFeature));
+    let hidden_edges: Vec<_> = g.edges.iter().filter(|e| e.from == "feature:_private" || e.to == "feature:_private").map(|e| (e.from.as_str(), e.to.as_str())).collect();
+    assert_eq!(hidden_edges, [("feature:_private", "dep:renamed"), ("feature:_private", "feature:alloc"), ("feature:derive", "feature:_private")]);
+    assert!(!g.edges.iter().any(|e| e.from == "feature:derive" && (e.to == "dep:renamed" || e.to == "feature:alloc")));
+    assert!(!g.to_dot().contains("fillcolor"));
+    assert!(!g.to_mermaid().contains("classDef build"));
+
+    let json = serde_json::to_string(&g).unwrap();
+    assert!(json.starts_with(r#"{"schema":1,"nodes":[{"id":"feature:_private","name":"_private","type":"hidden_feature","public":false},"#), "{json}");
+    assert!(json.contains(r#"{"id":"dep:renamed","name":"renamed","type":"dependency","public":true,"crate_name":"real","dep_kind":"normal"}"#), "{json}");
+}
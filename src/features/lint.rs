@@ -0,0 +1,243 @@
+use super::Features;
+use crate::Manifest;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::hash::BuildHasher;
+
+/// A problem found by [`Features::lint`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum FeatureLint<'a> {
+    /// An optional dependency created an implicit feature, and other features use it as a feature.
+    /// It's probably meant to be `dep:name` instead.
+    ImplicitFeatureReferenced { feature: &'a str, enabled_by: Vec<&'a str> },
+    /// These features enable each other in a loop
+    Cycle { features: Vec<&'a str> },
+    /// A `[[bin]]`, `[[example]]`, `[[test]]`, or `[[bench]]` requires a feature that doesn't exist
+    UndefinedRequiredFeature { section: &'static str, product: &'a str, feature: &'a str },
+    /// A feature enables another feature that doesn't exist
+    UnknownFeature { feature: &'a str, enables: &'a str },
+    /// `dep:name` or `name/feature` refers to a dependency that doesn't exist
+    UnknownDependency { feature: &'a str, dependency: &'a str },
+    /// `dep:name` used with a dependency that is not optional
+    DepSyntaxOnNonOptional { feature: &'a str, dependency: &'a str },
+    /// The `default` feature enables an optional dependency only as a side effect of `name/feature` syntax,
+    /// so it's always enabled anyway. It's probably meant to be `name?/feature`.
+    DefaultEnablesOptionalDependency { dependency: &'a str, via: Vec<&'a str> },
+    /// A hidden (`_`-prefixed) feature that no other feature nor any product uses
+    Unreachable { feature: &'a str },
+}
+
+impl FeatureLint<'_> {
+    /// Cargo refuses to build crates with this problem
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::UndefinedRequiredFeature { .. } | Self::UnknownFeature { .. } | Self::UnknownDependency { .. } | Self::DepSyntaxOnNonOptional { .. })
+    }
+}
+
+impl fmt::Display for FeatureLint<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ImplicitFeatureReferenced { feature, enabled_by } => write!(f, "optional dependency `{feature}` is used as a feature by `{}`; use `dep:{feature}` instead", enabled_by.join("`, `")),
+            Self::Cycle { features } => write!(f, "features enable each other in a loop: `{}`", features.join("` → `")),
+            Self::UndefinedRequiredFeature { section, product, feature } => write!(f, "[[{section}]] `{product}` requires feature `{feature}` that is not defined"),
+            Self::UnknownFeature { feature, enables } => write!(f, "feature `{feature}` includes `{enables}` which is neither a dependency nor another feature"),
+            Self::UnknownDependency { feature, dependency } => write!(f, "feature `{feature}` refers to `{dependency}` which is not a dependency"),
+            Self::DepSyntaxOnNonOptional { feature, dependency } => write!(f, "feature `{feature}` uses `dep:{dependency}`, but the dependency is not optional"),
+            Self::DefaultEnablesOptionalDependency { dependency, via } => write!(f, "optional dependency `{dependency}` is always enabled by default via `{}`; use `{dependency}?/…` to keep it optional", via.join("`, `")),
+            Self::Unreachable { feature } => write!(f, "feature `{feature}` is not used by any other feature"),
+        }
+    }
+}
+
+impl<'manifest, 'deps, S: BuildHasher + Default> Features<'manifest, 'deps, S> {
+    /// Check the features for mistakes. The `manifest` must be the one these features have been parsed from.
+    ///
+    /// Hidden features removed by the [`Resolver`](super::Resolver) have been inlined into the features that enabled them,
+    /// so problems in them are reported for their parents. Use [`Resolver::new_with_hasher_and_filter`](super::Resolver::new_with_hasher_and_filter) to keep them.
+    pub fn lint<M>(&self, manifest: &'manifest Manifest<M>) -> Vec<FeatureLint<'manifest>> where 'deps: 'manifest {
+        let mut out = Vec::new();
+        let sorted: BTreeMap<_, _> = self.features.iter().map(|(&k, f)| (k, f)).collect();
+
+        for (&key, f) in &sorted {
+            if !f.explicit && f.is_referenced() {
+                out.push(FeatureLint::ImplicitFeatureReferenced { feature: key, enabled_by: f.enabled_by.iter().copied().collect() });
+            }
+            for &enables in &f.enables_features {
+                if !self.features.contains_key(enables) && !self.hidden_features.contains_key(enables) {
+                    out.push(FeatureLint::UnknownFeature { feature: key, enables });
+                }
+            }
+            for (&dep_key, action) in &f.enables_deps {
+                match self.dependencies.get(dep_key) {
+                    None => out.push(FeatureLint::UnknownDependency { feature: key, dependency: dep_key }),
+                    Some(dep) if action.is_dep_only && !dep.dep().optional() => {
+                        out.push(FeatureLint::DepSyntaxOnNonOptional { feature: key, dependency: dep_key });
+                    },
+                    Some(_) => {},
+                }
+            }
+        }
+
+        out.extend(self.cycles().into_iter().map(|features| FeatureLint::Cycle { features }));
+
+        let products = [("bin", &manifest.bin), ("example", &manifest.example), ("test", &manifest.test), ("bench", &manifest.bench)];
+        let package_name = manifest.package.as_ref().map_or("", |p| p.name());
+        for (section, products) in products {
+            for p in products {
+                for feature in &p.required_features {
+                    // `dep/feature` is allowed too
+                    if !feature.contains('/') && !self.features.contains_key(feature.as_str()) && !self.hidden_features.contains_key(feature.as_str()) {
+                        out.push(FeatureLint::UndefinedRequiredFeature { section, product: p.name.as_deref().unwrap_or(package_name), feature });
+                    }
+                }
+            }
+        }
+
+        if let Some(default) = self.features.get("default") {
+            let (enabled, deps) = default.enables_recursive(&self.features);
+            let deps: BTreeMap<_, _> = deps.into_iter().collect();
+            for (dep_key, actions) in deps {
+                // the lookups are to get keys with the longer lifetime
+                let Some((&dep_key, dep)) = self.dependencies.get_key_value(dep_key) else { continue };
+                let only_via_slash = actions.iter().all(|(_, a)| !a.is_dep_only && !a.dep_features.is_empty());
+                // if the implicit feature is enabled by name, then it's deliberate
+                if dep.dep().optional() && only_via_slash && !enabled.contains_key(dep_key) {
+                    let via = actions.iter()
+                        .filter_map(|&(f, _)| self.features.get_key_value(f).map(|(&k, _)| k))
+                        .collect::<BTreeSet<_>>().into_iter().collect();
+                    out.push(FeatureLint::DefaultEnablesOptionalDependency { dependency: dep_key, via });
+                }
+            }
+        }
+
+        let mentioned: BTreeSet<&str> = manifest.features.iter()
+            .flat_map(|(k, actions)| actions.iter().filter(move |a| *a != k).map(|a| a.as_str()))
+            .chain([&manifest.bin, &manifest.example, &manifest.test, &manifest.bench].into_iter().flatten().flat_map(|p| p.required_features.iter().map(|f| f.as_str())))
+            .collect();
+        for key in sorted.keys().copied().chain(self.hidden_features.keys().copied()) {
+            if key.starts_with('_') && !mentioned.contains(key) && self.features.get(key).map_or(true, |f| !f.is_referenced()) {
+                out.push(FeatureLint::Unreachable { feature: key });
+            }
+        }
+
+        out
+    }
+
+    /// Groups of features that enable each other, sorted
+    fn cycles(&self) -> Vec<Vec<&'manifest str>> {
+        // Tarjan's strongly connected components
+        struct State<'a> {
+            index: BTreeMap<&'a str, (usize, usize)>,
+            stack: Vec<&'a str>,
+            on_stack: BTreeSet<&'a str>,
+            out: Vec<Vec<&'a str>>,
+        }
+        fn visit<'a, S: BuildHasher>(key: &'a str, features: &Features<'a, '_, S>, st: &mut State<'a>) {
+            let idx = st.index.len();
+            st.index.insert(key, (idx, idx));
+            st.stack.push(key);
+            st.on_stack.insert(key);
+            let mut self_loop = false;
+            if let Some(f) = features.features.get(key) {
+                for &next in &f.enables_features {
+                    if next == key {
+                        self_loop = true;
+                    } else if !st.index.contains_key(next) && features.features.contains_key(next) {
+                        visit(next, features, st);
+                        let low = st.index[next].1;
+                        let e = st.index.get_mut(key).unwrap();
+                        e.1 = e.1.min(low);
+                    } else if st.on_stack.contains(next) {
+                        let low = st.index[next].0;
+                        let e = st.index.get_mut(key).unwrap();
+                        e.1 = e.1.min(low);
+                    }
+                }
+            }
+            let (idx, low) = st.index[key];
+            if idx == low {
+                let mut group = Vec::new();
+                while let Some(k) = st.stack.pop() {
+                    st.on_stack.remove(k);
+                    group.push(k);
+                    if k == key {
+                        break;
+                    }
+                }
+                if group.len() > 1 || self_loop {
+                    group.sort_unstable();
+                    st.out.push(group);
+                }
+            }
+        }
+
+        let mut st = State { index: BTreeMap::new(), stack: Vec::new(), on_stack: BTreeSet::new(), out: Vec::new() };
+        let keys: BTreeSet<_> = self.features.keys().copied().collect();
+        for key in keys {
+            if !st.index.contains_key(key) {
+                visit(key, self, &mut st);
+            }
+        }
+        st.out.sort_unstable();
+        st.out
+    }
+}
+
+#[test]
+fn lints() {
+    let m = Manifest::from_str(r#"
+[package]
+name = "foo"
+
+[[bin]]
+name = "tool"
+required-features = ["cli", "missing", "serde/std"]
+
+[[example]]
+name = "ex"
+required-features = ["_for_example"]
+
+[dependencies]
+serde = { version = "1", optional = true }
+log = { version = "1", optional = true }
+regex = "1"
+quiet = { version = "1", optional = true }
+
+[features]
+default = ["serde/std", "quiet?/x"]
+cli = ["log", "a"]
+a = ["b"]
+b = ["a", "c"]
+c = ["c"]
+bad_dep = ["dep:regex", "nope/x", "dep:nothing"]
+bad_feature = ["undefined"]
+_unused = []
+_for_example = []
+"#).unwrap();
+    let r = super::Resolver::<std::collections::hash_map::RandomState>::new_with_hasher_and_filter(&|_| true);
+    let f = r.parse(&m);
+    let lints = f.lint(&m);
+    let has = |l: FeatureLint<'_>| assert!(lints.contains(&l), "{l:?} not in {lints:#?}");
+
+    has(FeatureLint::ImplicitFeatureReferenced { feature: "log", enabled_by: vec!["cli"] });
+    has(FeatureLint::ImplicitFeatureReferenced { feature: "serde", enabled_by: vec!["default"] });
+    has(FeatureLint::Cycle { features: vec!["a", "b"] });
+    has(FeatureLint::Cycle { features: vec!["c"] });
+    has(FeatureLint::UndefinedRequiredFeature { section: "bin", product: "tool", feature: "missing" });
+    has(FeatureLint::UnknownFeature { feature: "bad_feature", enables: "undefined" });
+    has(FeatureLint::UnknownDependency { feature: "bad_dep", dependency: "nope" });
+    has(FeatureLint::UnknownDependency { feature: "bad_dep", dependency: "nothing" });
+    has(FeatureLint::DepSyntaxOnNonOptional { feature: "bad_dep", dependency: "regex" });
+    has(FeatureLint::DefaultEnablesOptionalDependency { dependency: "serde", via: vec!["default"] });
+    has(FeatureLint::Unreachable { feature: "_unused" });
+    assert_eq!(lints.len(), 11, "{lints:#?}");
+    assert_eq!(lints.iter().filter(|l| l.is_error()).count(), 5);
+    assert_eq!(lints[0].to_string(), "feature `bad_dep` refers to `nope` which is not a dependency");
+
+    let f = super::Resolver::new().parse(&m);
+    let lints = f.lint(&m);
+    assert!(lints.contains(&FeatureLint::Unreachable { feature: "_unused" }));
+    assert!(!lints.contains(&FeatureLint::Unreachable { feature: "_for_example" }));
+}
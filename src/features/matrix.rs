@@ -0,0 +1,319 @@
+use super::Features;
+use std::collections::BTreeSet;
+use std::hash::BuildHasher;
+
+/// Generates combinations of features for testing, e.g. for a CI matrix. Make it with [`Features::matrix`].
+///
+/// All combinations skip features that aren't [user-facing](super::Feature::is_user_facing) and the `default` feature.
+/// Combinations that enable an identical set of features and dependencies as an earlier combination are skipped.
+#[derive(Debug, Clone)]
+pub struct FeatureMatrix<'f, 'a, S> {
+    features: &'f Features<'a, 'a, S>,
+    exclude: BTreeSet<&'a str>,
+    mutually_exclusive: Vec<BTreeSet<&'a str>>,
+    max_features: usize,
+    max_combinations: usize,
+}
+
+/// One set of features to test with
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub struct FeatureCombination<'a> {
+    /// Whether the `default` feature is enabled too
+    pub default_features: bool,
+    /// Features to enable
+    pub features: BTreeSet<&'a str>,
+}
+
+impl FeatureCombination<'_> {
+    /// Arguments for `cargo build`/`cargo test`
+    #[must_use]
+    pub fn cargo_args(&self) -> Vec<String> {
+        let mut args = Vec::with_capacity(3);
+        if !self.default_features {
+            args.push("--no-default-features".into());
+        }
+        if !self.features.is_empty() {
+            args.push("--features".into());
+            args.push(self.features.iter().copied().collect::<Vec<_>>().join(","));
+        }
+        args
+    }
+}
+
+impl<'manifest, S: BuildHasher + Default> Features<'manifest, 'manifest, S> {
+    /// Generator of feature combinations to test
+    #[must_use]
+    pub fn matrix(&self) -> FeatureMatrix<'_, 'manifest, S> {
+        FeatureMatrix {
+            features: self,
+            exclude: BTreeSet::new(),
+            mutually_exclusive: Vec::new(),
+            max_features: usize::MAX,
+            max_combinations: 1 << 12,
+        }
+    }
+}
+
+impl<'f, 'a, S: BuildHasher + Default> FeatureMatrix<'f, 'a, S> {
+    /// Never enable this feature explicitly (it may still be enabled by other features)
+    #[must_use]
+    pub fn exclude(mut self, feature: &'a str) -> Self {
+        self.exclude.insert(feature);
+        self
+    }
+
+    /// At most one of these features can be enabled at a time
+    #[must_use]
+    pub fn mutually_exclusive(mut self, group: impl IntoIterator<Item = &'a str>) -> Self {
+        self.mutually_exclusive.push(group.into_iter().collect());
+        self
+    }
+
+    /// Max number of explicitly enabled features in a single combination (unlimited by default)
+    #[must_use]
+    pub fn max_features(mut self, max: usize) -> Self {
+        self.max_features = max;
+        self
+    }
+
+    /// Stop after generating this many combinations (4096 by default)
+    #[must_use]
+    pub fn max_combinations(mut self, max: usize) -> Self {
+        self.max_combinations = max;
+        self
+    }
+
+    /// Features that can be combined, sorted
+    #[must_use]
+    pub fn candidates(&self) -> Vec<&'a str> {
+        let mut c: Vec<_> = self.features.features.iter()
+            .filter(|(&k, f)| k != "default" && f.is_user_facing() && !self.exclude.contains(k))
+            .map(|(&k, _)| k)
+            .collect();
+        c.sort_unstable();
+        c
+    }
+
+    /// All subsets of features, from the smallest, without `default`. It starts with an empty set.
+    #[must_use]
+    pub fn powerset(&self) -> Vec<FeatureCombination<'a>> {
+        let candidates = self.candidates();
+        let mut out = Combinations::new(self);
+        let max_size = self.max_features.min(candidates.len());
+        let mut indices = Vec::with_capacity(max_size);
+        for size in 0..=max_size {
+            indices.clear();
+            indices.extend(0..size);
+            loop {
+                if !out.push(false, indices.iter().map(|&i| candidates[i])) {
+                    return out.into_vec();
+                }
+                // next k-combination in lexicographic order
+                let Some(pos) = (0..size).rev().find(|&i| indices[i] != i + candidates.len() - size) else { break };
+                indices[pos] += 1;
+                for i in pos + 1..size {
+                    indices[i] = indices[i - 1] + 1;
+                }
+            }
+        }
+        out.into_vec()
+    }
+
+    /// Each feature alone, without `default`
+    #[must_use]
+    pub fn each_feature(&self) -> Vec<FeatureCombination<'a>> {
+        let mut out = Combinations::new(self);
+        for f in self.candidates() {
+            if !out.push(false, [f]) {
+                break;
+            }
+        }
+        out.into_vec()
+    }
+
+    /// Each feature together with the `default` feature. Features already enabled by `default` are skipped.
+    #[must_use]
+    pub fn each_feature_with_default(&self) -> Vec<FeatureCombination<'a>> {
+        let mut out = Combinations::new(self);
+        out.seen.insert(self.activation_set(true, &BTreeSet::new()));
+        for f in self.candidates() {
+            if !out.push(true, [f]) {
+                break;
+            }
+        }
+        out.into_vec()
+    }
+
+    /// A small set of combinations in which every pair of features that aren't mutually exclusive is enabled together at least once.
+    ///
+    /// Features that can't be paired with anything are included alone.
+    #[must_use]
+    pub fn pairwise(&self) -> Vec<FeatureCombination<'a>> {
+        let candidates = self.candidates();
+        let mut uncovered: BTreeSet<(&str, &str)> = BTreeSet::new();
+        for (i, &a) in candidates.iter().enumerate() {
+            for &b in &candidates[i + 1..] {
+                if !self.conflicts(&[a, b]) {
+                    uncovered.insert((a, b));
+                }
+            }
+        }
+        let alone: Vec<_> = candidates.iter().copied()
+            .filter(|&c| !uncovered.iter().any(|&(a, b)| a == c || b == c))
+            .collect();
+
+        let max_size = self.max_features.max(2);
+        let mut out = Combinations::new(self);
+        // Greedy: start from an uncovered pair, and keep adding the feature that covers the most uncovered pairs
+        while let Some(&(a, b)) = uncovered.iter().next() {
+            let mut set = vec![a, b];
+            while set.len() < max_size {
+                let best = candidates.iter().copied()
+                    .filter(|c| !set.contains(c))
+                    .map(|c| (set.iter().filter(|&&s| uncovered.contains(&ordered(s, c))).count(), c))
+                    .filter(|&(n, c)| n > 0 && !self.conflicts(&[&set[..], &[c]].concat()))
+                    .max_by_key(|&(n, c)| (n, std::cmp::Reverse(c)));
+                match best {
+                    Some((_, c)) => set.push(c),
+                    None => break,
+                }
+            }
+            for (i, &x) in set.iter().enumerate() {
+                for &y in &set[i + 1..] {
+                    uncovered.remove(&ordered(x, y));
+                }
+            }
+            if !out.push(false, set) {
+                return out.into_vec();
+            }
+        }
+        for f in alone {
+            if !out.push(false, [f]) {
+                break;
+            }
+        }
+        out.into_vec()
+    }
+
+    /// Features that will be active, including the dependencies they enable
+    fn activation_set(&self, default_features: bool, features: &BTreeSet<&'a str>) -> BTreeSet<String> {
+        let mut all = BTreeSet::new();
+        let roots = features.iter().copied().chain(default_features.then_some("default"));
+        for key in roots {
+            let Some(f) = self.features.features.get(key) else { continue };
+            let (enabled, deps) = f.enables_recursive(&self.features.features);
+            all.extend(enabled.into_keys().map(String::from));
+            for (dep, actions) in deps {
+                all.insert(format!("dep:{dep}"));
+                all.extend(actions.iter().flat_map(|(_, a)| &a.dep_features).map(|df| format!("{dep}/{df}")));
+            }
+        }
+        all
+    }
+
+    /// More than one of a mutually exclusive group would be active
+    fn conflicts(&self, features: &[&'a str]) -> bool {
+        if self.mutually_exclusive.is_empty() {
+            return false;
+        }
+        let active = self.activation_set(false, &features.iter().copied().collect());
+        self.mutually_exclusive_conflict(&active)
+    }
+
+    fn mutually_exclusive_conflict(&self, active: &BTreeSet<String>) -> bool {
+        self.mutually_exclusive.iter().any(|group| group.iter().filter(|&&g| active.contains(g)).count() > 1)
+    }
+}
+
+fn ordered<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+/// Deduplicating collector
+struct Combinations<'m, 'f, 'a, S> {
+    matrix: &'m FeatureMatrix<'f, 'a, S>,
+    seen: BTreeSet<BTreeSet<String>>,
+    out: Vec<FeatureCombination<'a>>,
+}
+
+impl<'m, 'f, 'a, S: BuildHasher + Default> Combinations<'m, 'f, 'a, S> {
+    fn new(matrix: &'m FeatureMatrix<'f, 'a, S>) -> Self {
+        Self { matrix, seen: BTreeSet::new(), out: Vec::new() }
+    }
+
+    /// Returns `false` if the limit has been reached
+    fn push(&mut self, default_features: bool, features: impl IntoIterator<Item = &'a str>) -> bool {
+        if self.out.len() >= self.matrix.max_combinations {
+            return false;
+        }
+        let features = features.into_iter().collect();
+        let active = self.matrix.activation_set(default_features, &features);
+        if !self.matrix.mutually_exclusive_conflict(&active) && self.seen.insert(active) {
+            self.out.push(FeatureCombination { default_features, features });
+        }
+        self.out.len() < self.matrix.max_combinations
+    }
+
+    fn into_vec(self) -> Vec<FeatureCombination<'a>> {
+        self.out
+    }
+}
+
+#[test]
+fn matrix() {
+    let m = crate::Manifest::from_str(r#"
+[package]
+name = "foo"
+
+[dependencies]
+serde = { version = "1", optional = true }
+
+[features]
+default = ["std"]
+std = []
+alloc = []
+full = ["std", "alloc"]
+tls-a = []
+tls-b = []
+_internal = []
+"#).unwrap();
+    let f = super::Resolver::new().parse(&m);
+
+    let matrix = f.matrix().exclude("full");
+    assert_eq!(matrix.candidates(), ["alloc", "serde", "std", "tls-a", "tls-b"]);
+
+    let each = matrix.each_feature();
+    assert_eq!(each.len(), 5);
+    assert_eq!(each[0].cargo_args(), ["--no-default-features", "--features", "alloc"]);
+
+    // std is already in default
+    let with_default = matrix.each_feature_with_default();
+    assert_eq!(with_default.len(), 4);
+    assert_eq!(with_default[0].cargo_args(), ["--features", "alloc"]);
+
+    let all = f.matrix().powerset();
+    // full + std or full + alloc is the same as full alone
+    assert_eq!(all.len(), 64 - 24, "{all:?}");
+    assert!(!all.iter().any(|c| c.features.contains("full") && c.features.contains("std")));
+    assert!(all[0].features.is_empty());
+
+    let limited = matrix.clone().max_features(1).powerset();
+    assert_eq!(limited.len(), 6);
+    assert_eq!(matrix.clone().max_combinations(3).powerset().len(), 3);
+
+    let exclusive = matrix.clone().mutually_exclusive(["tls-a", "tls-b"]);
+    let all = exclusive.powerset();
+    assert!(!all.iter().any(|c| c.features.contains("tls-a") && c.features.contains("tls-b")));
+    assert_eq!(all.len(), 3 * 8);
+
+    let pairs = exclusive.pairwise();
+    let candidates = exclusive.candidates();
+    for (i, &a) in candidates.iter().enumerate() {
+        for &b in &candidates[i + 1..] {
+            let together = pairs.iter().any(|c| c.features.contains(a) && c.features.contains(b));
+            assert_eq!(together, (a, b) != ("tls-a", "tls-b"), "{a} {b} {pairs:?}");
+        }
+    }
+    assert!(pairs.len() <= 3, "{pairs:?}");
+}
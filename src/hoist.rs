@@ -0,0 +1,258 @@
+use crate::workspace::normalize;
+use crate::{Dependency, DependencyDetail, DepsSet, InheritedDependencyDetail, Kind, Manifest, Workspace, WorkspaceMember};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Suggestions for moving dependencies shared by workspace members into `[workspace.dependencies]`.
+///
+/// This is the reverse of the workspace inheritance. See [`DependencyHoisting::analyze`].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct DependencyHoisting {
+    /// Dependencies that can be inherited from the workspace, sorted by their key
+    pub hoist: Vec<HoistedDependency>,
+    /// Crates that members require in semver-incompatible versions
+    pub conflicts: Vec<VersionConflict>,
+}
+
+/// A new entry for `[workspace.dependencies]`
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HoistedDependency {
+    /// Key in the dependencies tables
+    pub key: String,
+    /// The common part of the dependency, to add to `[workspace.dependencies]`. Its `path` is relative to the workspace root.
+    pub template: Dependency,
+    /// Where it's used, and member-specific settings that remain in the members
+    pub members: Vec<HoistedEntry>,
+}
+
+/// Replacement of a member's dependency with `{ workspace = true }`
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct HoistedEntry {
+    /// Package name of the member
+    pub member: String,
+    /// Which `[dependencies]` section it's in
+    pub kind: Kind,
+    /// `[target."cfg".dependencies]`
+    pub target: Option<String>,
+    /// Features not in the template, and `optional`
+    pub inherited: InheritedDependencyDetail,
+}
+
+/// The same crate is required with incompatible version requirements
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VersionConflict {
+    /// Name of the crate (not the key, which may be renamed)
+    pub crate_name: String,
+    /// Package names of the members and their version requirements
+    pub requirements: Vec<(String, String)>,
+}
+
+struct Entry<'a> {
+    member: &'a str,
+    kind: Kind,
+    target: Option<&'a str>,
+    dep: &'a Dependency,
+    /// Relative to the workspace root
+    path: Option<PathBuf>,
+}
+
+impl DependencyHoisting {
+    /// Find dependencies that are declared in more than one member with compatible requirements.
+    ///
+    /// `members` are from [`Manifest::load_workspace_members`] of the workspace in `workspace_dir`.
+    /// Dependencies must come from the same source, and have the same key, `default-features`, and other settings.
+    /// The template gets the highest version requirement, and features common to all members.
+    /// Dependencies already in `[workspace.dependencies]` are not changed.
+    #[must_use]
+    pub fn analyze<M, N>(workspace: &Manifest<M>, workspace_dir: &Path, members: &[WorkspaceMember<N>]) -> Self {
+        let existing = workspace.workspace.as_ref().map(|ws| &ws.dependencies);
+        let mut by_key = BTreeMap::<&str, Vec<Entry<'_>>>::new();
+        for member in members {
+            let m = &member.manifest;
+            let sections = [(&m.dependencies, Kind::Normal, None), (&m.build_dependencies, Kind::Build, None), (&m.dev_dependencies, Kind::Dev, None)].into_iter()
+                .chain(m.target.iter().flat_map(|(cfg, t)| [
+                    (&t.dependencies, Kind::Normal, Some(cfg.as_str())),
+                    (&t.build_dependencies, Kind::Build, Some(cfg.as_str())),
+                    (&t.dev_dependencies, Kind::Dev, Some(cfg.as_str())),
+                ]));
+            for (deps, kind, target) in sections {
+                for (key, dep) in deps {
+                    let detail = dep.detail();
+                    if matches!(dep, Dependency::Inherited(_)) || detail.is_some_and(|d| d.inherited) {
+                        continue;
+                    }
+                    let path = detail.and_then(|d| d.path.as_deref()).map(|p| relative_to(&normalize(&member.dir.join(p)), &normalize(workspace_dir)));
+                    by_key.entry(key).or_default().push(Entry { member: member.name(), kind, target, dep, path });
+                }
+            }
+        }
+
+        let mut out = Self::default();
+        let mut requirements_by_crate = BTreeMap::<(&str, Option<&str>), Vec<(&str, &str)>>::new();
+        for (&key, entries) in &by_key {
+            for e in entries.iter().filter(|e| e.dep.is_crates_io() || e.dep.detail().is_some_and(|d| d.registry.is_some())) {
+                let name = e.dep.package().unwrap_or(key);
+                requirements_by_crate.entry((name, e.dep.detail().and_then(|d| d.registry.as_deref()))).or_default().push((e.member, e.dep.req()));
+            }
+        }
+        for ((crate_name, _), mut reqs) in requirements_by_crate {
+            reqs.sort_unstable();
+            reqs.dedup();
+            if !reqs.iter().all(|&(_, a)| reqs.iter().all(|&(_, b)| requirements_compatible(a, b))) {
+                out.conflicts.push(VersionConflict {
+                    crate_name: crate_name.into(),
+                    requirements: reqs.into_iter().map(|(m, r)| (m.into(), r.into())).collect(),
+                });
+            }
+        }
+
+        for (key, entries) in by_key {
+            let mut members: Vec<_> = entries.iter().map(|e| e.member).collect();
+            members.sort_unstable();
+            members.dedup();
+            if members.len() < 2 || existing.is_some_and(|deps| deps.contains_key(key)) {
+                continue;
+            }
+            if let Some(hoisted) = Self::hoist(key, &entries) {
+                out.hoist.push(hoisted);
+            }
+        }
+        out
+    }
+
+    fn hoist(key: &str, entries: &[Entry<'_>]) -> Option<HoistedDependency> {
+        let details: Vec<DependencyDetail> = entries.iter().map(|e| {
+            let mut d = e.dep.detail().cloned().unwrap_or_else(|| DependencyDetail { version: Some(e.dep.req().into()), ..Default::default() });
+            d.path = e.path.as_ref().map(|p| p.display().to_string());
+            d
+        }).collect();
+
+        // everything except these must be the same
+        let source = |d: &DependencyDetail| DependencyDetail { version: None, features: Vec::new(), optional: false, ..d.clone() };
+        let first = &details[0];
+        if details.iter().any(|d| source(d) != source(first)) {
+            return None;
+        }
+        let versions: Vec<&str> = details.iter().filter_map(|d| d.version.as_deref()).collect();
+        if !versions.iter().all(|a| versions.iter().all(|b| requirements_compatible(a, b))) {
+            return None;
+        }
+        let version = versions.iter().copied().max_by_key(|v| parse_requirement(v).map(|(_, n)| n)).map(String::from);
+        let common_features: Vec<String> = first.features.iter().filter(|f| details.iter().all(|d| d.features.contains(f))).cloned().collect();
+
+        let template = DependencyDetail { version, features: common_features.clone(), optional: false, ..source(first) };
+        let template = if template == (DependencyDetail { version: template.version.clone(), ..Default::default() }) {
+            Dependency::Simple(template.version.unwrap_or_default())
+        } else {
+            Dependency::Detailed(Box::new(template))
+        };
+
+        Some(HoistedDependency {
+            key: key.into(),
+            template,
+            members: entries.iter().zip(&details).map(|(e, d)| HoistedEntry {
+                member: e.member.into(),
+                kind: e.kind,
+                target: e.target.map(From::from),
+                inherited: InheritedDependencyDetail {
+                    features: d.features.iter().filter(|f| !common_features.contains(f)).cloned().collect(),
+                    optional: d.optional,
+                    workspace: true,
+                },
+            }).collect(),
+        })
+    }
+
+    /// Add the templates to `[workspace.dependencies]`, and make the members inherit them.
+    ///
+    /// `members` must be the ones that have been analyzed.
+    pub fn apply<M, N>(&self, workspace: &mut Workspace<M>, members: &mut [WorkspaceMember<N>]) {
+        for h in &self.hoist {
+            workspace.dependencies.insert(h.key.clone(), h.template.clone());
+            for e in &h.members {
+                let Some(member) = members.iter_mut().find(|m| m.name() == e.member) else { continue };
+                if let Some(dep) = deps_section_mut(&mut member.manifest, e.kind, e.target.as_deref()).and_then(|deps| deps.get_mut(&h.key)) {
+                    *dep = Dependency::Inherited(e.inherited.clone());
+                }
+            }
+        }
+    }
+}
+
+fn deps_section_mut<'m, M>(manifest: &'m mut Manifest<M>, kind: Kind, target: Option<&str>) -> Option<&'m mut DepsSet> {
+    Some(match (kind, target) {
+        (Kind::Normal, None) => &mut manifest.dependencies,
+        (Kind::Build, None) => &mut manifest.build_dependencies,
+        (Kind::Dev, None) => &mut manifest.dev_dependencies,
+        (Kind::Normal, Some(t)) => &mut manifest.target.get_mut(t)?.dependencies,
+        (Kind::Build, Some(t)) => &mut manifest.target.get_mut(t)?.build_dependencies,
+        (Kind::Dev, Some(t)) => &mut manifest.target.get_mut(t)?.dev_dependencies,
+    })
+}
+
+/// Operator and numbers of a single-comparator requirement (missing numbers are 0)
+fn parse_requirement(req: &str) -> Option<(&str, [u64; 3])> {
+    let req = req.trim();
+    let start = req.find(|c: char| c.is_ascii_digit())?;
+    let op = req[..start].trim();
+    let version = req[start..].split(['-', '+']).next()?;
+    let mut numbers = [0; 3];
+    for (i, n) in version.split('.').enumerate() {
+        *numbers.get_mut(i)? = n.parse().ok()?;
+    }
+    Some((op, numbers))
+}
+
+/// Number of leading version components that must match for semver compatibility, like `1` for `1.2`, and `2` for `0.2`.
+fn compatible_prefix(req: &str) -> Option<Vec<u64>> {
+    let (op, _) = parse_requirement(req)?;
+    if !matches!(op, "" | "^") {
+        return None;
+    }
+    let numbers: Vec<u64> = req.trim().trim_start_matches('^').trim().split(['-', '+']).next()?.split('.').filter_map(|n| n.parse().ok()).collect();
+    let significant = numbers.iter().position(|&n| n != 0).map_or(numbers.len(), |i| i + 1);
+    Some(numbers[..significant].to_vec())
+}
+
+/// Can be unified into a single version of the crate
+fn requirements_compatible(a: &str, b: &str) -> bool {
+    if a.trim() == b.trim() {
+        return true;
+    }
+    match (compatible_prefix(a), compatible_prefix(b)) {
+        (Some(a), Some(b)) => a.starts_with(&b) || b.starts_with(&a),
+        _ => false,
+    }
+}
+
+/// `path` as relative to `base`, both already normalized
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let mut path_c = path.components().peekable();
+    let mut base_c = base.components().peekable();
+    while let (Some(a), Some(b)) = (path_c.peek(), base_c.peek()) {
+        if a != b {
+            break;
+        }
+        path_c.next();
+        base_c.next();
+    }
+    base_c.map(|_| Component::ParentDir).chain(path_c).collect()
+}
+
+#[test]
+fn requirements() {
+    assert!(requirements_compatible("1", "1.5.2"));
+    assert!(requirements_compatible("^1.2", "1.5"));
+    assert!(requirements_compatible("0.3", "0.3.1"));
+    assert!(requirements_compatible("=1.2", "=1.2"));
+    assert!(!requirements_compatible("0.3", "0.4"));
+    assert!(!requirements_compatible("1", "2"));
+    assert!(!requirements_compatible("=1.2", "1.2"));
+    assert!(!requirements_compatible("0.0.1", "0.0.2"));
+    assert_eq!(relative_to(Path::new("ws/crates/a"), Path::new("ws")), Path::new("crates/a"));
+    assert_eq!(relative_to(Path::new("other/b"), Path::new("ws/x")), Path::new("../../other/b"));
+}
@@ -0,0 +1,430 @@
+//! Conversion of a [`Manifest`] to and from an entry of a [registry index](https://doc.rust-lang.org/cargo/reference/registry-index.html), the format used by crates.io and alternative registries.
+//!
+//! The index has one JSON object per line per published version. Use [`IndexEntry::from_manifest`] to make one,
+//! and [`IndexEntry::from_json`] or [`IndexEntry::from_index_file`] to read them back.
+
+use crate::features::{Features, Kind, ParseDependency, Resolver};
+use crate::{Dependency, DependencyDetail, DepsSet, Error, FeatureSet, Manifest};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+/// A single version of a crate in the registry index (one line of the index file).
+///
+/// The field names are the same as in the index JSON, even where they're inconsistent with `Cargo.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct IndexEntry {
+    /// Name of the package
+    pub name: String,
+    /// Version of the package (semver)
+    pub vers: String,
+    /// Normal, build, and dev dependencies, including target-specific ones
+    pub deps: Vec<IndexDependency>,
+    /// SHA256 of the `.crate` file
+    pub cksum: String,
+    /// Features that use only the old syntax
+    pub features: FeatureSet,
+    /// Set in the index, not in `Cargo.toml`
+    #[serde(default)]
+    pub yanked: bool,
+    /// The `package.links` value
+    #[serde(default)]
+    pub links: Option<String>,
+    /// Index format version. It's `2` if `features2` is used.
+    #[serde(default = "default_index_version")]
+    pub v: u32,
+    /// Features that use `dep:` or `?` syntax, which older Cargo versions can't parse.
+    #[serde(default, skip_serializing_if = "FeatureSet::is_empty")]
+    pub features2: FeatureSet,
+    /// MSRV from `package.rust-version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<String>,
+}
+
+/// A dependency as listed in the registry index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct IndexDependency {
+    /// The key from `[dependencies]`. If the dependency has been renamed, the actual crate name is in `package`.
+    pub name: String,
+    /// Semver requirement
+    pub req: String,
+    /// Features of the dependency to enable
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Whether it's an optional dependency
+    #[serde(default)]
+    pub optional: bool,
+    /// Enable the `default` features of the dependency
+    #[serde(default = "default_true")]
+    pub default_features: bool,
+    /// `cfg()` or target triple of `[target.….dependencies]`
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Normal, build, or dev dependency
+    #[serde(default)]
+    pub kind: Kind,
+    /// URL of the registry index, if it's not the same registry as the package's
+    #[serde(default)]
+    pub registry: Option<String>,
+    /// Actual crate name, if the dependency has been renamed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+}
+
+/// Maps a registry alias (or `None` for the default registry) to the index URL
+type RegistryUrl = dyn Fn(Option<&str>) -> Result<Option<String>, Error>;
+
+fn default_index_version() -> u32 {
+    1
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl IndexEntry {
+    /// Make an index entry for the package in the manifest.
+    ///
+    /// The manifest must be complete, i.e. with workspace inheritance applied (see [`Manifest::complete_from_path`]).
+    /// `cksum` is the SHA256 of the `.crate` tarball, which this crate can't compute.
+    ///
+    /// Dependencies that use `registry = "alias"` are not supported, because aliases are defined in Cargo's config.
+    /// Use [`IndexEntry::from_manifest_with_registries`] to resolve them.
+    pub fn from_manifest<M>(manifest: &Manifest<M>, cksum: impl Into<String>) -> Result<Self, Error> {
+        Self::from_manifest_with_registries(manifest, cksum, &|alias| match alias {
+            None => Ok(None),
+            Some(_) => Err(Error::Other("dependency uses a registry alias, and its index URL is unknown")),
+        })
+    }
+
+    /// Make an index entry for the package in the manifest, translating registries of dependencies to index URLs.
+    ///
+    /// `registry_url` is called with `None` for dependencies from the default registry (crates.io),
+    /// and with `Some(alias)` for dependencies with `registry = "alias"`. It should return the index URL of the registry,
+    /// or `None` if it's the same registry that the package is published to.
+    ///
+    /// For publishing to crates.io, `None` should map to `None`. For an alternative registry,
+    /// `None` should map to `Some("https://github.com/rust-lang/crates.io-index")`.
+    pub fn from_manifest_with_registries<M>(manifest: &Manifest<M>, cksum: impl Into<String>, registry_url: &RegistryUrl) -> Result<Self, Error> {
+        let package = manifest.package.as_ref().ok_or(Error::Other("not a package"))?;
+
+        let mut deps = Vec::new();
+        add_deps(&mut deps, &manifest.dependencies, Kind::Normal, None, registry_url)?;
+        add_deps(&mut deps, &manifest.build_dependencies, Kind::Build, None, registry_url)?;
+        add_deps(&mut deps, &manifest.dev_dependencies, Kind::Dev, None, registry_url)?;
+        for (target, t) in &manifest.target {
+            add_deps(&mut deps, &t.dependencies, Kind::Normal, Some(target), registry_url)?;
+            add_deps(&mut deps, &t.build_dependencies, Kind::Build, Some(target), registry_url)?;
+            add_deps(&mut deps, &t.dev_dependencies, Kind::Dev, Some(target), registry_url)?;
+        }
+
+        let (features2, features) = manifest.features.iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .partition::<FeatureSet, _>(|(_, actions)| actions.iter().any(|a| uses_new_feature_syntax(a)));
+
+        Ok(Self {
+            name: package.name.clone(),
+            vers: package.version.get()?.clone(),
+            deps,
+            cksum: cksum.into(),
+            v: if features2.is_empty() { 1 } else { 2 },
+            features,
+            features2,
+            yanked: false,
+            links: package.links.clone(),
+            rust_version: package.rust_version.as_ref().map(|r| r.get().cloned()).transpose()?,
+        })
+    }
+
+    /// One line of the index file
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("index entry is always serializable")
+    }
+
+    /// Parse one line of the index file
+    pub fn from_json(line: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(line)
+    }
+
+    /// Parse an index file (git or sparse), which has all versions of a crate, one per line
+    pub fn from_index_file(content: &str) -> Result<Vec<Self>, serde_json::Error> {
+        content.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Self::from_json)
+            .collect()
+    }
+
+    /// Owned features and dependencies in the form needed by [`Resolver::parse_custom`].
+    ///
+    /// Call [`FeaturesInput::parse`] on the result to get the [`Features`].
+    #[must_use]
+    pub fn features_input(&self) -> FeaturesInput {
+        let mut dependencies: Vec<_> = self.deps.iter()
+            .map(|d| (d.clone(), d.to_dependency()))
+            .collect();
+        // parse_custom keeps the first occurrence, so the order must match `Resolver::parse`:
+        // normal, build, then both per target, then dev, then dev per target
+        dependencies.sort_by(|(a, _), (b, _)| (a.kind == Kind::Dev, a.target.as_deref(), a.kind).cmp(&(b.kind == Kind::Dev, b.target.as_deref(), b.kind)));
+
+        FeaturesInput {
+            features: self.all_features().into_iter().collect(),
+            dependencies,
+        }
+    }
+
+    /// All features, with `features2` merged into `features`
+    #[must_use]
+    pub fn all_features(&self) -> FeatureSet {
+        let mut all = self.features.clone();
+        all.extend(self.features2.iter().map(|(k, v)| (k.clone(), v.clone())));
+        all
+    }
+}
+
+impl IndexDependency {
+    /// Convert to a `Cargo.toml`-style dependency. The key for it is in `name`.
+    #[must_use]
+    pub fn to_dependency(&self) -> Dependency {
+        if self.features.is_empty() && !self.optional && self.default_features && self.registry.is_none() && self.package.is_none() {
+            return Dependency::Simple(self.req.clone());
+        }
+        Dependency::Detailed(Box::new(DependencyDetail {
+            version: Some(self.req.clone()),
+            package: self.package.clone(),
+            registry_index: self.registry.clone(),
+            features: self.features.clone(),
+            optional: self.optional,
+            default_features: self.default_features,
+            ..DependencyDetail::default()
+        }))
+    }
+
+    /// Actual name of the crate, which may be different from the `name` key if the dependency has been renamed
+    #[must_use]
+    pub fn crate_name(&self) -> &str {
+        self.package.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Data from an [`IndexEntry`] owned in a way that can be borrowed by [`Resolver::parse_custom`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FeaturesInput {
+    /// Both `features` and `features2`
+    pub features: HashMap<String, Vec<String>>,
+    /// In the order of [`Resolver::parse`] (normal > build > dev, untargeted first), with the `Cargo.toml`-style equivalent
+    pub dependencies: Vec<(IndexDependency, Dependency)>,
+}
+
+impl FeaturesInput {
+    /// Dependencies in the form taken by [`Resolver::parse_custom`]
+    pub fn parse_dependencies(&self) -> impl Iterator<Item = ParseDependency<'_, '_>> {
+        self.dependencies.iter().map(|(d, dep)| ParseDependency {
+            key: &d.name,
+            kind: d.kind,
+            target: d.target.as_deref(),
+            dep,
+        })
+    }
+
+    /// Run the resolver on this data
+    pub fn parse<'a, S: BuildHasher + Default>(&'a self, resolver: &Resolver<'_, S>) -> Features<'a, 'a, S> {
+        resolver.parse_custom(&self.features, self.parse_dependencies())
+    }
+}
+
+/// `dep:` and `?/` can't be parsed by Cargo older than 1.60
+fn uses_new_feature_syntax(action: &str) -> bool {
+    action.starts_with("dep:") || action.contains("?/")
+}
+
+fn add_deps(out: &mut Vec<IndexDependency>, deps: &DepsSet, kind: Kind, target: Option<&str>, registry_url: &RegistryUrl) -> Result<(), Error> {
+    for (key, dep) in deps {
+        let d = match dep {
+            Dependency::Simple(req) => {
+                out.push(IndexDependency {
+                    name: key.clone(),
+                    req: req.clone(),
+                    features: Vec::new(),
+                    optional: false,
+                    default_features: true,
+                    target: target.map(From::from),
+                    kind,
+                    registry: registry_url(None)?,
+                    package: None,
+                });
+                continue;
+            },
+            Dependency::Detailed(d) => d,
+            Dependency::Inherited(_) => return Err(Error::InheritedUnknownValue),
+        };
+
+        let Some(req) = &d.version else {
+            // Cargo strips path and git dev-dependencies when publishing
+            if kind == Kind::Dev {
+                continue;
+            }
+            return Err(Error::Other("dependencies need a version to be published"));
+        };
+
+        let registry = if let Some(index) = &d.registry_index {
+            Some(index.clone())
+        } else {
+            registry_url(d.registry.as_deref())?
+        };
+
+        out.push(IndexDependency {
+            name: key.clone(),
+            req: req.clone(),
+            features: d.features.clone(),
+            optional: d.optional,
+            default_features: d.default_features,
+            target: target.map(From::from),
+            kind,
+            registry,
+            package: d.package.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[test]
+fn index_entry() {
+    let m = Manifest::from_str(r#"
+[package]
+name = "foo"
+version = "1.2.3"
+links = "z"
+rust-version = "1.70"
+
+[dependencies]
+simple = "1"
+renamed = { version = "2.0", package = "real", default-features = false, features = ["x"] }
+opt = { version = "3", optional = true }
+other_reg = { version = "4", registry-index = "sparse+https://example.com/index/" }
+
+[dev-dependencies]
+local_only = { path = "../local" }
+dev = "0.1"
+
+[target.'cfg(unix)'.build-dependencies]
+unix = "5"
+
+[features]
+default = ["a"]
+a = ["opt"]
+b = ["dep:opt"]
+c = ["renamed?/y"]
+"#).unwrap();
+    let e = IndexEntry::from_manifest(&m, "abc").unwrap();
+    assert_eq!(e.name, "foo");
+    assert_eq!(e.vers, "1.2.3");
+    assert_eq!(e.cksum, "abc");
+    assert_eq!(e.links.as_deref(), Some("z"));
+    assert_eq!(e.rust_version.as_deref(), Some("1.70"));
+    assert_eq!(e.v, 2);
+    assert_eq!(e.features.keys().collect::<Vec<_>>(), ["a", "default"]);
+    assert_eq!(e.features2.keys().collect::<Vec<_>>(), ["b", "c"]);
+    assert_eq!(e.all_features().len(), 4);
+
+    assert_eq!(e.deps.len(), 6);
+    assert!(!e.deps.iter().any(|d| d.name == "local_only"));
+    let renamed = e.deps.iter().find(|d| d.name == "renamed").unwrap();
+    assert_eq!(renamed.package.as_deref(), Some("real"));
+    assert!(!renamed.default_features);
+    assert_eq!(renamed.features, ["x"]);
+    assert!(e.deps.iter().find(|d| d.name == "opt").unwrap().optional);
+    assert_eq!(e.deps.iter().find(|d| d.name == "other_reg").unwrap().registry.as_deref(), Some("sparse+https://example.com/index/"));
+    let unix = e.deps.iter().find(|d| d.name == "unix").unwrap();
+    assert_eq!(unix.kind, Kind::Build);
+    assert_eq!(unix.target.as_deref(), Some("cfg(unix)"));
+    assert_eq!(e.deps.iter().find(|d| d.name == "dev").unwrap().kind, Kind::Dev);
+
+    let json = e.to_json();
+    assert!(json.contains(r#""kind":"build""#), "{json}");
+    assert!(json.contains(r#""v":2"#), "{json}");
+    assert!(!json.contains('\n'));
+
+    let alias = Manifest::from_str("[package]\nname = \"a\"\n[dependencies]\nb = { version = \"1\", registry = \"mine\" }").unwrap();
+    assert!(IndexEntry::from_manifest(&alias, "").is_err());
+    let e = IndexEntry::from_manifest_with_registries(&alias, "", &|r| Ok(r.map(|r| format!("https://{r}.example.com")))).unwrap();
+    assert_eq!(e.deps[0].registry.as_deref(), Some("https://mine.example.com"));
+    assert_eq!(e.v, 1);
+}
+
+#[test]
+fn index_entry_parse() {
+    let file = r#"
+{"name":"foo","vers":"0.1.0","deps":[],"cksum":"00","features":{},"yanked":true,"links":null}
+{"name":"foo","vers":"1.0.0","deps":[{"name":"dev_opt","req":"^1","features":[],"optional":true,"default_features":true,"target":null,"kind":"dev","registry":null},{"name":"opt","req":"^1","features":["x"],"optional":true,"default_features":false,"target":"cfg(unix)","kind":"normal","registry":null,"package":"real"},{"name":"plain","req":"^2","features":[],"optional":false,"default_features":true,"target":null,"kind":"build","registry":null}],"cksum":"01","features":{"default":["opt"]},"features2":{"hidden":["dep:plain","opt?/y"]},"yanked":false,"links":null,"v":2}
+"#;
+    let entries = IndexEntry::from_index_file(file).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].yanked);
+    assert_eq!(entries[0].v, 1);
+
+    let e = &entries[1];
+    assert_eq!(e.deps[1].crate_name(), "real");
+    assert!(matches!(e.deps[2].to_dependency(), Dependency::Simple(ref r) if r == "^2"));
+    let opt = e.deps[1].to_dependency();
+    assert!(opt.optional());
+    assert_eq!(opt.package(), Some("real"));
+    assert!(!opt.detail().unwrap().default_features);
+
+    let input = e.features_input();
+    assert_eq!(input.features.len(), 2);
+    assert_eq!(input.dependencies.iter().map(|(d, _)| d.name.as_str()).collect::<Vec<_>>(), ["plain", "opt", "dev_opt"]);
+
+    let f = input.parse(&Resolver::new());
+    assert!(f.features.contains_key("opt"));
+    assert!(f.features.contains_key("dev_opt"));
+    assert!(f.features["hidden"].enables_deps["opt"].is_conditional);
+    assert_eq!(f.features["opt"].enabled_by.iter().copied().collect::<Vec<_>>(), ["default"]);
+    assert_eq!(f.dependencies["opt"].crate_name, "real");
+    assert_eq!(f.dependencies["plain"].detail().1, Kind::Build);
+    assert_eq!(f.dependencies["opt"].targets.keys().next().unwrap().target, Some("cfg(unix)"));
+
+    let roundtrip = IndexEntry::from_json(&e.to_json()).unwrap();
+    assert_eq!(&roundtrip, e);
+}
+
+#[test]
+fn index_dependency_order() {
+    let m = Manifest::from_str(r#"
+[package]
+name = "foo"
+version = "1.0.0"
+
+[dependencies]
+x = { version = "1", package = "normal", optional = true }
+
+[dev-dependencies]
+y = { version = "1", package = "dev", optional = true }
+
+[target.'cfg(a)'.build-dependencies]
+y = { version = "1", package = "a-build", optional = true }
+z = { version = "1", package = "a-build", optional = true }
+
+[target.'cfg(b)'.dependencies]
+x = "1"
+z = { version = "1", package = "b-normal" }
+
+[target.'cfg(b)'.dev-dependencies]
+x = "1"
+"#).unwrap();
+    let from_manifest = Resolver::new().parse(&m);
+    let input = IndexEntry::from_manifest(&m, "").unwrap().features_input();
+    let from_index = input.parse(&Resolver::new());
+    assert_eq!(from_manifest.dependencies.len(), from_index.dependencies.len());
+    for (key, dep) in &from_manifest.dependencies {
+        let index_dep = &from_index.dependencies[key];
+        assert_eq!(dep.crate_name, index_dep.crate_name, "{key}");
+        assert_eq!(dep.targets.keys().collect::<Vec<_>>(), index_dep.targets.keys().collect::<Vec<_>>(), "{key}");
+    }
+    assert_eq!(from_index.dependencies["y"].crate_name, "a-build");
+    assert_eq!(from_index.dependencies["z"].targets.len(), 2);
+}
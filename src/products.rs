@@ -0,0 +1,266 @@
+use crate::{AbstractFilesystem, CrateType, Edition, Error, Manifest, OptionalFile, Product};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Section of `Cargo.toml` a [`Product`] is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ProductKind {
+    /// `[lib]`
+    Lib,
+    /// `[[bin]]`
+    Bin,
+    /// `[[example]]`
+    Example,
+    /// `[[test]]`
+    Test,
+    /// `[[bench]]`
+    Bench,
+    /// `package.build`
+    BuildScript,
+}
+
+impl ProductKind {
+    /// Name of the section in `Cargo.toml`, like `"bin"`, or `"build"` for the build script
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lib => "lib",
+            Self::Bin => "bin",
+            Self::Example => "example",
+            Self::Test => "test",
+            Self::Bench => "bench",
+            Self::BuildScript => "build",
+        }
+    }
+}
+
+impl fmt::Display for ProductKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A problem with `[lib]`, `[[bin]]`, etc. that Cargo would refuse to build. See [`Manifest::check_products`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum ProductProblem {
+    /// A `[[bin]]`, `[[example]]`, etc. has no name
+    MissingName { kind: ProductKind, path: Option<String> },
+    /// More than one product of the same kind has this name
+    DuplicateName { kind: ProductKind, name: String },
+    /// A binary has the same name as the library, and the library's `crate-type` makes their files collide
+    BinNamedLikeLib { name: String },
+    /// `lib.name` must be a valid Rust identifier (no `-`)
+    InvalidLibName { name: String },
+    /// The source file doesn't exist
+    MissingFile { kind: ProductKind, name: Option<String>, path: String },
+    /// `proc-macro = true` can't be combined with other crate types
+    ProcMacroWithCrateType { crate_type: Vec<CrateType> },
+    /// `package.default-run` isn't a name of any binary
+    UnknownDefaultRun { name: String },
+}
+
+impl fmt::Display for ProductProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingName { kind, path: Some(path) } => write!(f, "[[{kind}]] for `{path}` is missing a name"),
+            Self::MissingName { kind, path: None } => write!(f, "[[{kind}]] is missing a name"),
+            Self::DuplicateName { kind, name } => write!(f, "more than one [[{kind}]] is named `{name}`"),
+            Self::BinNamedLikeLib { name } => write!(f, "binary `{name}` has the same name as the library, and their files would collide"),
+            Self::InvalidLibName { name } => write!(f, "library name `{name}` is not a valid Rust identifier"),
+            Self::MissingFile { kind, name, path } => write!(f, "{kind} `{}` points to `{path}`, which doesn't exist", name.as_deref().unwrap_or_default()),
+            Self::ProcMacroWithCrateType { crate_type } => {
+                let crate_type: Vec<_> = crate_type.iter().map(CrateType::as_str).collect();
+                write!(f, "proc-macro library can't also have crate-type {crate_type:?}")
+            },
+            Self::UnknownDefaultRun { name } => write!(f, "default-run `{name}` is not a binary of this package"),
+        }
+    }
+}
+
+/// A product with Cargo's defaults filled in. See [`Manifest::products`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ResolvedProduct {
+    /// Section it's from
+    pub kind: ProductKind,
+    /// Name of the target, as used by `cargo build --bin <name>`. Binaries keep `-` in their names.
+    pub name: String,
+    /// Name of the crate passed to rustc, with `-` replaced by `_`
+    pub crate_name: String,
+    /// Path to the root source file, relative to the package's directory (unless it was set to an absolute path)
+    pub path: PathBuf,
+    /// The product's own edition, or the package's
+    pub edition: Edition,
+    /// Never empty. Libraries default to `lib` (or `proc-macro`), and other products to `bin`.
+    pub crate_type: Vec<CrateType>,
+    /// Features that must be enabled to build it
+    pub required_features: Vec<String>,
+}
+
+impl ResolvedProduct {
+    /// Names of the files that Cargo puts in its output directory (e.g. `target/release`, or `target/release/examples` for examples) when building for `target_os`,
+    /// which is a value like [`std::env::consts::OS`].
+    ///
+    /// Tests, benches and build scripts aren't copied there, so they have none.
+    #[must_use]
+    pub fn file_names(&self, target_os: &str) -> Vec<String> {
+        if matches!(self.kind, ProductKind::Test | ProductKind::Bench | ProductKind::BuildScript) {
+            return Vec::new();
+        }
+        self.crate_type.iter().filter_map(|t| match t {
+            CrateType::Bin => t.file_name(&self.name, target_os),
+            _ => t.file_name(&self.crate_name, target_os),
+        }).collect()
+    }
+}
+
+impl<Metadata> Manifest<Metadata> {
+    /// All products of the package, including the build script, with names, paths and crate types set the way Cargo would set them.
+    ///
+    /// Call it after [`Manifest::complete_from_abstract_filesystem`], so that autodiscovered products and the build script are included.
+    /// Products that don't have a name (other than the library) are skipped. Fails if the edition is inherited from a workspace that hasn't been loaded.
+    pub fn products(&self) -> Result<Vec<ResolvedProduct>, Error> {
+        let Some(package) = &self.package else { return Ok(Vec::new()) };
+        let package_edition = *package.edition.get()?;
+        let mut out = Vec::new();
+
+        for (kind, p) in self.products_by_kind() {
+            let name = match (kind, &p.name) {
+                (_, Some(name)) => name.clone(),
+                (ProductKind::Lib, None) => package.name.replace('-', "_"),
+                _ => continue,
+            };
+            let path = match (&p.path, kind) {
+                (Some(path), _) => path.clone(),
+                (None, ProductKind::Lib) => "src/lib.rs".into(),
+                (None, ProductKind::Bin) if name == package.name => "src/main.rs".into(),
+                (None, ProductKind::Bin) => format!("src/bin/{name}.rs"),
+                (None, ProductKind::Example) => format!("examples/{name}.rs"),
+                (None, ProductKind::Test) => format!("tests/{name}.rs"),
+                (None, _) => format!("benches/{name}.rs"),
+            };
+            // completion sets `crate-type = ["lib"]`, which `proc-macro = true` overrides
+            let crate_type = match (kind, p.crate_type.as_slice()) {
+                (ProductKind::Lib, [] | [CrateType::Lib]) if p.proc_macro => vec![CrateType::ProcMacro],
+                (ProductKind::Lib, []) => vec![CrateType::Lib],
+                (ProductKind::Lib | ProductKind::Example, types) if !types.is_empty() => types.to_vec(),
+                _ => vec![CrateType::Bin],
+            };
+            out.push(ResolvedProduct {
+                kind,
+                crate_name: name.replace('-', "_"),
+                name,
+                path: path.into(),
+                edition: p.edition.unwrap_or(package_edition),
+                crate_type,
+                required_features: if kind == ProductKind::Lib { Vec::new() } else { p.required_features.clone() },
+            });
+        }
+
+        if let Some(OptionalFile::Path(path)) = &package.build {
+            out.push(ResolvedProduct {
+                kind: ProductKind::BuildScript,
+                name: "build-script-build".into(),
+                crate_name: "build_script_build".into(),
+                path: path.clone(),
+                edition: package_edition,
+                crate_type: vec![CrateType::Bin],
+                required_features: Vec::new(),
+            });
+        }
+        Ok(out)
+    }
+
+    /// `[lib]` and then `[[bin]]`, `[[example]]`, `[[test]]`, `[[bench]]` in their order
+    pub(crate) fn products_by_kind(&self) -> impl Iterator<Item = (ProductKind, &Product)> {
+        let sections = [(ProductKind::Bin, &self.bin), (ProductKind::Example, &self.example), (ProductKind::Test, &self.test), (ProductKind::Bench, &self.bench)];
+        self.lib.iter().map(|p| (ProductKind::Lib, p))
+            .chain(sections.into_iter().flat_map(|(kind, products)| products.iter().map(move |p| (kind, p))))
+    }
+
+    /// Check `[lib]`, `[[bin]]`, `[[example]]`, `[[test]]`, and `[[bench]]` for conflicts and missing files.
+    ///
+    /// Call it after [`Manifest::complete_from_abstract_filesystem`], with the same filesystem, so that the autodiscovered products are checked too.
+    /// Fails only if the filesystem can't be read.
+    pub fn check_products<Fs: AbstractFilesystem>(&self, fs: Fs) -> Result<Vec<ProductProblem>, Error> {
+        let mut out = Vec::new();
+
+        if let Some(lib) = &self.lib {
+            if let Some(name) = &lib.name {
+                if !is_identifier(name) {
+                    out.push(ProductProblem::InvalidLibName { name: name.clone() });
+                }
+                // these have platform-specific file names without the `lib` prefix
+                let collides = lib.proc_macro || lib.crate_type.iter().any(|t| matches!(t, CrateType::Dylib | CrateType::Cdylib | CrateType::ProcMacro));
+                if collides && self.bin.iter().any(|b| b.name.as_ref() == Some(name)) {
+                    out.push(ProductProblem::BinNamedLikeLib { name: name.clone() });
+                }
+            }
+            if lib.proc_macro && lib.crate_type.iter().any(CrateType::conflicts_with_proc_macro) {
+                out.push(ProductProblem::ProcMacroWithCrateType { crate_type: lib.crate_type.clone() });
+            }
+        }
+
+        let sections = [(ProductKind::Bin, &self.bin), (ProductKind::Example, &self.example), (ProductKind::Test, &self.test), (ProductKind::Bench, &self.bench)];
+        for (kind, products) in sections {
+            let mut names = BTreeMap::<&str, usize>::new();
+            for p in products {
+                match &p.name {
+                    Some(name) => *names.entry(name).or_default() += 1,
+                    None => out.push(ProductProblem::MissingName { kind, path: p.path.clone() }),
+                }
+            }
+            out.extend(names.into_iter().filter(|&(_, n)| n > 1).map(|(name, _)| ProductProblem::DuplicateName { kind, name: name.into() }));
+        }
+
+        for (kind, p) in self.products_by_kind() {
+            let Some(path) = &p.path else { continue };
+            if !file_exists(&fs, path)? {
+                out.push(ProductProblem::MissingFile { kind, name: p.name.clone(), path: path.clone() });
+            }
+        }
+
+        if let Some(name) = self.package.as_ref().and_then(|p| p.default_run.as_ref()) {
+            if !self.bin.iter().any(|b: &Product| b.name.as_ref() == Some(name)) {
+                out.push(ProductProblem::UnknownDefaultRun { name: name.clone() });
+            }
+        }
+
+        out.sort_unstable();
+        out.dedup();
+        Ok(out)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_') && name != "_"
+}
+
+/// Absolute paths and paths outside of the package are assumed to exist
+fn file_exists(fs: &dyn AbstractFilesystem, path: &str) -> Result<bool, Error> {
+    let path = path.trim_start_matches("./");
+    if path.starts_with('/') || path.split('/').any(|c| c == "..") {
+        return Ok(true);
+    }
+    let (dir, file_name) = path.rsplit_once('/').unwrap_or(("", path));
+    match fs.file_names_in(dir) {
+        Ok(files) => Ok(files.contains(file_name)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[test]
+fn identifiers() {
+    assert!(is_identifier("foo_bar"));
+    assert!(is_identifier("_x2"));
+    assert!(!is_identifier("foo-bar"));
+    assert!(!is_identifier("2x"));
+    assert!(!is_identifier("_"));
+    assert!(!is_identifier(""));
+}
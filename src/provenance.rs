@@ -0,0 +1,211 @@
+use crate::{Dependency, Inheritable, InheritedDependencyDetail, Kind, LintGroups, Manifest, Package, Value};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem::take;
+use std::path::{Path, PathBuf};
+
+/// Which values of a [`Manifest`](crate::Manifest) have been copied from the root workspace manifest.
+///
+/// It's filled in by [`Manifest::complete_from_path`](crate::Manifest::complete_from_path) and similar methods. It's empty for manifests that haven't been completed.
+/// It's not a part of the manifest's data, so it's ignored by `PartialEq`.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Provenance {
+    /// Directory of the workspace's root `Cargo.toml`. It's empty if the manifest is the workspace root itself, and `None` if nothing has been inherited.
+    pub workspace_root: Option<PathBuf>,
+    /// Keys of `[package]` fields copied from `[workspace.package]`, like `license` or `rust-version`
+    pub package_fields: BTreeSet<String>,
+    /// Dependencies copied from `[workspace.dependencies]`: their section, `[target]`, and key
+    pub dependencies: BTreeSet<(Kind, Option<String>, String)>,
+    /// `[lints]` have been copied from `[workspace.lints]`
+    pub lints: bool,
+
+    /// Values as they were inherited, to detect local changes
+    package_values: BTreeMap<String, Value>,
+    dependency_values: BTreeMap<(Kind, Option<String>, String), (InheritedDependencyDetail, Dependency)>,
+    lints_value: Option<LintGroups>,
+}
+
+impl PartialEq for Provenance {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+/// Where a value has been defined. See [`Provenance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueSource<'a> {
+    /// In the package's own `Cargo.toml`
+    Local,
+    /// In `[workspace.package]` of the `Cargo.toml` in this directory
+    WorkspacePackage(&'a Path),
+    /// In `[workspace.dependencies]` of the `Cargo.toml` in this directory
+    WorkspaceDependencies(&'a Path),
+    /// In `[workspace.lints]` of the `Cargo.toml` in this directory
+    WorkspaceLints(&'a Path),
+}
+
+impl ValueSource<'_> {
+    /// The `Cargo.toml` file to edit to change this value, given the path of the package's own `Cargo.toml`
+    #[must_use]
+    pub fn manifest_path(&self, package_manifest_path: &Path) -> PathBuf {
+        match *self {
+            Self::Local => package_manifest_path.into(),
+            Self::WorkspacePackage(dir) | Self::WorkspaceDependencies(dir) | Self::WorkspaceLints(dir) => {
+                if dir.as_os_str().is_empty() { package_manifest_path.into() } else { dir.join("Cargo.toml") }
+            },
+        }
+    }
+}
+
+impl Provenance {
+    /// Source of a `[package]` field, by its key in `Cargo.toml`, e.g. `"license-file"`
+    #[must_use]
+    pub fn package_field(&self, key: &str) -> ValueSource<'_> {
+        match &self.workspace_root {
+            Some(root) if self.package_fields.contains(key) => ValueSource::WorkspacePackage(root),
+            _ => ValueSource::Local,
+        }
+    }
+
+    /// Source of a dependency, by its section and key. `target` is the `cfg()` or target triple for `[target.….dependencies]`.
+    #[must_use]
+    pub fn dependency(&self, kind: Kind, target: Option<&str>, key: &str) -> ValueSource<'_> {
+        match &self.workspace_root {
+            Some(root) if self.dependencies.iter().any(|(k, t, d)| *k == kind && t.as_deref() == target && d == key) => ValueSource::WorkspaceDependencies(root),
+            _ => ValueSource::Local,
+        }
+    }
+
+    /// Source of the `[lints]` table
+    #[must_use]
+    pub fn lints(&self) -> ValueSource<'_> {
+        match &self.workspace_root {
+            Some(root) if self.lints => ValueSource::WorkspaceLints(root),
+            _ => ValueSource::Local,
+        }
+    }
+
+    /// Nothing has been inherited
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.workspace_root.is_none()
+    }
+
+    pub(crate) fn add_dependency(&mut self, kind: Kind, target: Option<&str>, key: &str, overrides: InheritedDependencyDetail, inherited: &Dependency) {
+        let id = (kind, target.map(From::from), key.to_owned());
+        self.dependencies.insert(id.clone());
+        self.dependency_values.insert(id, (overrides, inherited.clone()));
+    }
+
+    pub(crate) fn add_lints(&mut self, lints: &LintGroups) {
+        self.lints = true;
+        self.lints_value = Some(lints.clone());
+    }
+
+    pub(crate) fn snapshot_package_fields<M>(&mut self, package: &mut Package<M>) {
+        for key in &self.package_fields {
+            if let Some(value) = package_field(package, key).and_then(|f| f.to_value()) {
+                self.package_values.insert(key.clone(), value);
+            }
+        }
+    }
+
+    pub(crate) fn merge(&mut self, other: Self) {
+        if other.workspace_root.is_some() {
+            self.workspace_root = other.workspace_root;
+        }
+        self.package_fields.extend(other.package_fields);
+        self.dependencies.extend(other.dependencies);
+        self.lints |= other.lints;
+        self.package_values.extend(other.package_values);
+        self.dependency_values.extend(other.dependency_values);
+        self.lints_value = other.lints_value.or(self.lints_value.take());
+    }
+}
+
+impl<Metadata> Manifest<Metadata> {
+    /// Undo the workspace inheritance, so that the manifest can be serialized back to `Cargo.toml` with `{ workspace = true }`.
+    ///
+    /// Values that have been inherited from the workspace by [`Manifest::complete_from_path`] and similar methods
+    /// are changed back to [`Inheritable::Inherited`] and [`Dependency::Inherited`] (with their local `features` and `optional`),
+    /// unless they have been modified since. Modified values stay as they are, and become local.
+    ///
+    /// Afterwards the [`provenance`](Manifest::provenance) is empty, and the manifest [needs workspace inheritance](Manifest::needs_workspace_inheritance) again.
+    pub fn restore_workspace_inheritance(&mut self) {
+        let provenance = take(&mut self.provenance);
+
+        if let Some(package) = &mut self.package {
+            for (key, value) in &provenance.package_values {
+                if let Some(field) = package_field(package, key) {
+                    if field.to_value().as_ref() == Some(value) {
+                        field.set_inherited();
+                    }
+                }
+            }
+        }
+
+        for ((kind, target, key), (overrides, inherited)) in provenance.dependency_values {
+            let deps = match (kind, target) {
+                (Kind::Normal, None) => Some(&mut self.dependencies),
+                (Kind::Build, None) => Some(&mut self.build_dependencies),
+                (Kind::Dev, None) => Some(&mut self.dev_dependencies),
+                (kind, Some(target)) => self.target.get_mut(&target).map(|t| match kind {
+                    Kind::Normal => &mut t.dependencies,
+                    Kind::Build => &mut t.build_dependencies,
+                    Kind::Dev => &mut t.dev_dependencies,
+                }),
+            };
+            if let Some(dep) = deps.and_then(|deps| deps.get_mut(&key)) {
+                if *dep == inherited {
+                    *dep = Dependency::Inherited(overrides);
+                }
+            }
+        }
+
+        if let (Some(lints), Inheritable::Set(current)) = (provenance.lints_value, &self.lints) {
+            if *current == lints {
+                self.lints = Inheritable::Inherited;
+            }
+        }
+    }
+}
+
+/// Type-erased `Inheritable<T>`
+trait InheritableField {
+    fn to_value(&self) -> Option<Value>;
+    fn set_inherited(&mut self);
+}
+
+impl<T: Serialize> InheritableField for Inheritable<T> {
+    fn to_value(&self) -> Option<Value> {
+        Value::try_from(self.get().ok()?).ok()
+    }
+
+    fn set_inherited(&mut self) {
+        *self = Self::Inherited;
+    }
+}
+
+/// By its key in `Cargo.toml`
+fn package_field<'p, M>(package: &'p mut Package<M>, key: &str) -> Option<&'p mut dyn InheritableField> {
+    Some(match key {
+        "authors" => &mut package.authors,
+        "categories" => &mut package.categories,
+        "description" => package.description.as_mut()?,
+        "documentation" => package.documentation.as_mut()?,
+        "edition" => &mut package.edition,
+        "exclude" => &mut package.exclude,
+        "homepage" => package.homepage.as_mut()?,
+        "include" => &mut package.include,
+        "keywords" => &mut package.keywords,
+        "license" => package.license.as_mut()?,
+        "license-file" => package.license_file.as_mut()?,
+        "publish" => &mut package.publish,
+        "readme" => &mut package.readme,
+        "repository" => package.repository.as_mut()?,
+        "rust-version" => package.rust_version.as_mut()?,
+        "version" => &mut package.version,
+        _ => return None,
+    })
+}
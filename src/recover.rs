@@ -0,0 +1,222 @@
+use crate::{Error, Manifest, Value};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, Deserializer, IntoDeserializer, Visitor};
+use serde::Deserialize;
+use std::fmt;
+
+/// A part of `Cargo.toml` that has been skipped by [`Manifest::from_str_lossy`]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SectionError {
+    /// Keys of the tables leading to the skipped value, e.g. `["package", "edition"]`. Arrays of tables use indices, e.g. `["bin", "0"]`.
+    pub path: Vec<String>,
+    /// Why it couldn't be parsed
+    pub error: Error,
+}
+
+impl fmt::Display for SectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.join("."), self.error)
+    }
+}
+
+impl Manifest<Value> {
+    /// Best-effort parsing, for manifests of third-party crates that may be invalid.
+    ///
+    /// Values that fail to parse are removed, so their fields get default values (if a required field like `package.name` is invalid, the whole `[package]` is removed).
+    /// Returns the partial manifest, and errors for every removed value.
+    ///
+    /// Fails only if the file isn't valid TOML.
+    pub fn from_str_lossy(cargo_toml_content: &str) -> Result<(Self, Vec<SectionError>), Error> {
+        let mut table: toml::Table = toml::from_str(cargo_toml_content)?;
+        let mut errors = Vec::new();
+        if Self::deserialize(Value::Table(table.clone())).is_err() {
+            let doc = table.clone();
+            let mut path = Vec::new();
+            remove_invalid(&doc, &mut path, &mut table, &mut errors);
+        }
+        Ok((Self::from_table(table)?, errors))
+    }
+}
+
+/// A step in the path to a value
+#[derive(Clone)]
+enum Key {
+    Table(String),
+    Index(usize),
+}
+
+fn remove_invalid(doc: &toml::Table, path: &mut Vec<Key>, table: &mut toml::Table, errors: &mut Vec<SectionError>) {
+    table.retain(|key, value| {
+        path.push(Key::Table(key.into()));
+        let keep = recover(doc, path, value, errors);
+        path.pop();
+        keep
+    });
+}
+
+/// Returns `false` if the value can't be fixed by removing its parts
+fn recover(doc: &toml::Table, path: &mut Vec<Key>, value: &mut Value, errors: &mut Vec<SectionError>) -> bool {
+    if probe(doc, path, value, true).is_ok() {
+        return true;
+    }
+    match value {
+        Value::Table(table) => remove_invalid(doc, path, table, errors),
+        Value::Array(items) if items.iter().all(Value::is_table) => {
+            let mut i = 0;
+            items.retain_mut(|item| {
+                path.push(Key::Index(i));
+                i += 1;
+                let keep = recover(doc, path, item, errors);
+                path.pop();
+                keep
+            });
+        },
+        _ => {},
+    }
+    match probe(doc, path, value, true) {
+        Ok(()) => true,
+        Err(e) => {
+            let path = path.iter().map(|k| match k {
+                Key::Table(k) => k.clone(),
+                Key::Index(i) => i.to_string(),
+            }).collect();
+            let error: toml::de::Error = match e {
+                ProbeError::MissingField(field) => de::Error::missing_field(field),
+                ProbeError::Other(msg) => de::Error::custom(msg),
+            };
+            errors.push(SectionError { path, error: error.into() });
+            false
+        },
+    }
+}
+
+/// Parse the value alone, in a manifest without anything else.
+///
+/// If the parent tables need a field, like `package.name`, it's copied from the original document, if it's valid there.
+/// With `fill = false` missing fields are assumed to be in the parents.
+fn probe(doc: &toml::Table, path: &[Key], value: &Value, fill: bool) -> Result<(), ProbeError> {
+    // depth of the parent table, and the key copied to it
+    let mut filled: Vec<(usize, &'static str)> = Vec::new();
+    loop {
+        let mut wrapped = value.clone();
+        for (depth, key) in path.iter().enumerate().rev() {
+            wrapped = match key {
+                Key::Table(key) => {
+                    let parent = table_at(doc, &path[..depth]);
+                    let mut t: toml::Table = filled.iter().filter(|&&(d, _)| d == depth)
+                        .filter_map(|&(_, k)| Some((k.to_string(), parent?.get(k)?.clone())))
+                        .collect();
+                    t.insert(key.clone(), wrapped);
+                    Value::Table(t)
+                },
+                Key::Index(_) => Value::Array(vec![wrapped]),
+            };
+        }
+        let field = match Manifest::<Value>::deserialize(ValueDeserializer(&wrapped)) {
+            Ok(_) => return Ok(()),
+            Err(ProbeError::MissingField(_)) if !fill => return Ok(()),
+            Err(ProbeError::MissingField(field)) => field,
+            Err(e) => return Err(e),
+        };
+
+        // the innermost parent that has the field
+        let parent = (0..path.len()).rev().find(|&depth| {
+            !filled.contains(&(depth, field)) && !matches!(&path[depth], Key::Table(k) if k == field)
+                && table_at(doc, &path[..depth]).is_some_and(|t| t.contains_key(field))
+        });
+        let Some(depth) = parent else { return Err(ProbeError::MissingField(field)) };
+        let mut field_path = path[..depth].to_vec();
+        field_path.push(Key::Table(field.into()));
+        let field_value = table_at(doc, &path[..depth]).and_then(|t| t.get(field)).ok_or(ProbeError::MissingField(field))?;
+        if probe(doc, &field_path, field_value, false).is_err() {
+            // the parent is invalid anyway, so the value can't be checked
+            return Ok(());
+        }
+        filled.push((depth, field));
+    }
+}
+
+fn table_at<'a>(doc: &'a toml::Table, path: &[Key]) -> Option<&'a toml::Table> {
+    let Some((Key::Table(first), rest)) = path.split_first() else {
+        return path.is_empty().then_some(doc);
+    };
+    rest.iter().try_fold(doc.get(first)?, |value, key| match key {
+        Key::Table(key) => value.as_table()?.get(key),
+        Key::Index(i) => value.as_array()?.get(*i),
+    })?.as_table()
+}
+
+/// Keeps missing fields apart from other errors
+#[derive(Debug)]
+enum ProbeError {
+    MissingField(&'static str),
+    Other(String),
+}
+
+impl de::Error for ProbeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Other(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::MissingField(field)
+    }
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing field `{field}`"),
+            Self::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+/// Like `toml::Value` as a `Deserializer`, but with `ProbeError`
+struct ValueDeserializer<'a>(&'a Value);
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_> {
+    type Error = ProbeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        match self.0 {
+            Value::String(s) => visitor.visit_str(s),
+            Value::Integer(i) => visitor.visit_i64(*i),
+            Value::Float(f) => visitor.visit_f64(*f),
+            Value::Boolean(b) => visitor.visit_bool(*b),
+            Value::Datetime(d) => visitor.visit_string(d.to_string()),
+            Value::Array(items) => visitor.visit_seq(SeqDeserializer::new(items.iter().map(ValueDeserializer))),
+            Value::Table(t) => visitor.visit_map(MapDeserializer::new(t.iter().map(|(k, v)| (k.as_str(), ValueDeserializer(v))))),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, ProbeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, ProbeError> {
+        match self.0 {
+            Value::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, ProbeError> for ValueDeserializer<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
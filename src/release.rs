@@ -0,0 +1,274 @@
+use crate::workspace::normalize;
+use crate::{Dependency, DepsSet, Error, Inheritable, Manifest, WorkspaceMember};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Which part of the version to increase. See [`ReleasePlan::version_bump`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VersionBump {
+    /// `1.2.3` → `2.0.0`, and `2.0.0-rc.1` → `2.0.0`
+    Major,
+    /// `1.2.3` → `1.3.0`, and `1.3.0-rc.1` → `1.3.0`
+    Minor,
+    /// `1.2.3` → `1.2.4`, and `1.2.4-rc.1` → `1.2.4`
+    Patch,
+    /// Pre-release with this label: `1.2.3` → `1.2.4-label.1`, and `1.2.4-label.1` → `1.2.4-label.2`
+    Prerelease(String),
+}
+
+/// A simple semver version. Build metadata is discarded on bumps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: String,
+}
+
+impl Version {
+    fn parse(version: &str) -> Result<Self, Error> {
+        let version = version.trim();
+        let version = version.split_once('+').map_or(version, |(v, _build)| v);
+        let (numbers, pre) = version.split_once('-').unwrap_or((version, ""));
+        let mut numbers = numbers.split('.').map(|n| n.parse::<u64>().map_err(|_| Error::Other("invalid version number")));
+        let mut next = || numbers.next().ok_or(Error::Other("version must have three numbers"))?;
+        let v = Self { major: next()?, minor: next()?, patch: next()?, pre: pre.into() };
+        if numbers.next().is_some() {
+            return Err(Error::Other("version must have three numbers"));
+        }
+        Ok(v)
+    }
+
+    fn bump(&self, bump: &VersionBump) -> Self {
+        let is_pre = !self.pre.is_empty();
+        let (major, minor, patch) = (self.major, self.minor, self.patch);
+        let (major, minor, patch, pre) = match bump {
+            VersionBump::Major if is_pre && minor == 0 && patch == 0 => (major, 0, 0, String::new()),
+            VersionBump::Major => (major + 1, 0, 0, String::new()),
+            VersionBump::Minor if is_pre && patch == 0 => (major, minor, 0, String::new()),
+            VersionBump::Minor => (major, minor + 1, 0, String::new()),
+            VersionBump::Patch if is_pre => (major, minor, patch, String::new()),
+            VersionBump::Patch => (major, minor, patch + 1, String::new()),
+            VersionBump::Prerelease(label) => {
+                let current = self.pre.strip_prefix(label.as_str()).and_then(|rest| if rest.is_empty() { Some(0) } else { rest.strip_prefix('.')?.parse::<u64>().ok() });
+                match current {
+                    Some(n) => (major, minor, patch, format!("{label}.{}", n + 1)),
+                    // a different label continues the same pre-release
+                    None if is_pre => (major, minor, patch, format!("{label}.1")),
+                    None => (major, minor, patch + 1, format!("{label}.1")),
+                }
+            },
+        };
+        Self { major, minor, patch, pre }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre)?;
+        }
+        Ok(())
+    }
+}
+
+/// Changes to make to `Cargo.toml` files to release new versions of workspace members
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReleasePlan {
+    /// Packages getting a new version: name, old version, new version.
+    ///
+    /// There's more than one if the version is inherited from the workspace.
+    pub packages: Vec<(String, String, String)>,
+    /// Every change to every manifest, in order
+    pub edits: Vec<ManifestEdit>,
+}
+
+/// Change of a single string value in a `Cargo.toml` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ManifestEdit {
+    /// Path to the `Cargo.toml` file
+    pub manifest_path: PathBuf,
+    /// Keys of nested tables, e.g. `["dependencies", "serde", "version"]`
+    pub toml_path: Vec<String>,
+    /// The current value
+    pub old: String,
+    /// The value to set
+    pub new: String,
+}
+
+impl fmt::Display for ManifestEdit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} = \"{}\" → \"{}\"", self.manifest_path.display(), self.toml_path.join("."), self.old, self.new)
+    }
+}
+
+impl ReleasePlan {
+    /// Plan a new version of the workspace member `package`, and update requirements on it in all other members
+    /// and in `[workspace.dependencies]`.
+    ///
+    /// `members` are from [`Manifest::load_workspace_members`] of the workspace in `workspace_dir`.
+    /// The manifests are re-read from disk, because the loaded ones already have workspace inheritance applied.
+    ///
+    /// If the package inherits `version.workspace = true`, then the workspace's version is bumped,
+    /// and it applies to all other members that inherit it too.
+    ///
+    /// Only requirements with a single comparator, like `1.2` or `=1.2.3`, are updated. Wildcards and ranges are left as-is.
+    pub fn version_bump<M>(workspace_dir: &Path, members: &[WorkspaceMember<M>], package: &str, bump: &VersionBump) -> Result<Self, Error> {
+        let root_path = workspace_dir.join("Cargo.toml");
+        let root = read_uncompleted(&root_path)?;
+        let ws_version = root.workspace.as_ref().and_then(|ws| ws.package.as_ref()).and_then(|p| p.version.as_deref());
+
+        let raw = members.iter().map(|m| {
+            let path = m.dir.join("Cargo.toml");
+            let manifest = read_uncompleted(&path).map_err(|e| Error::WorkspaceMember(Box::new((e, m.dir.clone()))))?;
+            Ok((m, path, manifest))
+        }).collect::<Result<Vec<_>, Error>>()?;
+
+        let (_, bumped_path, bumped_manifest) = raw.iter().find(|(m, ..)| m.name() == package)
+            .ok_or_else(|| Error::WorkspaceIntegrity(format!("`{package}` is not a workspace member")))?;
+        let version = &bumped_manifest.package.as_ref().ok_or(Error::Other("not a package"))?.version;
+
+        let mut plan = Self { packages: Vec::new(), edits: Vec::new() };
+        // name -> (member's dir, new version)
+        let mut new_versions = BTreeMap::new();
+        match version {
+            Inheritable::Set(old) => {
+                let new = Version::parse(old)?.bump(bump).to_string();
+                plan.edit(bumped_path, &["package", "version"], old, &new);
+                plan.packages.push((package.into(), old.clone(), new.clone()));
+                new_versions.insert(package, (bumped_path.parent().unwrap_or(workspace_dir), new));
+            },
+            Inheritable::Inherited => {
+                let old = ws_version.ok_or_else(|| Error::WorkspaceIntegrity("workspace.package.version is missing".into()))?;
+                let new = Version::parse(old)?.bump(bump).to_string();
+                plan.edit(&root_path, &["workspace", "package", "version"], old, &new);
+                for (m, path, manifest) in &raw {
+                    if manifest.package.as_ref().is_some_and(|p| !p.version.is_set()) {
+                        plan.packages.push((m.name().into(), old.into(), new.clone()));
+                        new_versions.insert(m.name(), (path.parent().unwrap_or(workspace_dir), new.clone()));
+                    }
+                }
+            },
+        }
+
+        let is_bumped = |dependant_dir: &Path, dep_path: &str| {
+            let dir = normalize(&dependant_dir.join(dep_path));
+            new_versions.values().find(|(d, _)| normalize(d) == dir).map(|(_, v)| v.as_str())
+        };
+        if let Some(ws) = &root.workspace {
+            plan.update_requirements(&root_path, &["workspace", "dependencies"], &ws.dependencies, workspace_dir, &is_bumped);
+        }
+        for (m, path, manifest) in &raw {
+            for (section, deps) in [("dependencies", &manifest.dependencies), ("build-dependencies", &manifest.build_dependencies), ("dev-dependencies", &manifest.dev_dependencies)] {
+                plan.update_requirements(path, &[section], deps, &m.dir, &is_bumped);
+            }
+            for (cfg, t) in &manifest.target {
+                for (section, deps) in [("dependencies", &t.dependencies), ("build-dependencies", &t.build_dependencies), ("dev-dependencies", &t.dev_dependencies)] {
+                    plan.update_requirements(path, &["target", cfg, section], deps, &m.dir, &is_bumped);
+                }
+            }
+        }
+        Ok(plan)
+    }
+
+    fn update_requirements<'a>(&mut self, manifest_path: &Path, table: &[&str], deps: &DepsSet, dir: &Path, is_bumped: &dyn Fn(&Path, &str) -> Option<&'a str>) {
+        for (key, dep) in deps {
+            let Dependency::Detailed(d) = dep else { continue };
+            let (Some(path), Some(req)) = (&d.path, &d.version) else { continue };
+            let Some(new_version) = is_bumped(dir, path) else { continue };
+            if let Some(new_req) = updated_requirement(req, new_version) {
+                let toml_path: Vec<_> = table.iter().copied().chain([key.as_str(), "version"]).collect();
+                self.edit(manifest_path, &toml_path, req, &new_req);
+            }
+        }
+    }
+
+    fn edit(&mut self, manifest_path: &Path, toml_path: &[&str], old: &str, new: &str) {
+        if old != new {
+            self.edits.push(ManifestEdit {
+                manifest_path: manifest_path.into(),
+                toml_path: toml_path.iter().map(|&s| s.into()).collect(),
+                old: old.into(),
+                new: new.into(),
+            });
+        }
+    }
+
+    /// Apply the edits for `manifest_path` to the TOML source of that file, preserving formatting and comments.
+    #[cfg(feature = "edit")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "edit")))]
+    pub fn apply_to_toml(&self, manifest_path: &Path, toml_source: &str) -> Result<String, Error> {
+        crate::edit::apply_edits(toml_source, self.edits.iter().filter(|e| e.manifest_path == manifest_path))
+    }
+
+    /// Write all the edits to disk, preserving formatting and comments.
+    ///
+    /// All files are checked before any is modified. It fails if any of the old values don't match.
+    #[cfg(feature = "edit")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "edit")))]
+    pub fn apply(&self) -> Result<(), Error> {
+        let files: std::collections::BTreeSet<&Path> = self.edits.iter().map(|e| e.manifest_path.as_path()).collect();
+        let updated = files.into_iter().map(|path| {
+            let source = std::fs::read_to_string(path)?;
+            Ok((path, self.apply_to_toml(path, &source)?))
+        }).collect::<Result<Vec<_>, Error>>()?;
+        for (path, toml) in updated {
+            std::fs::write(path, toml)?;
+        }
+        Ok(())
+    }
+}
+
+/// Without workspace inheritance applied
+fn read_uncompleted(manifest_path: &Path) -> Result<Manifest, Error> {
+    Manifest::from_slice(&std::fs::read(manifest_path)?)
+}
+
+/// Keep the operator, change the version. `None` if it can't be changed.
+fn updated_requirement(req: &str, new_version: &str) -> Option<String> {
+    let req = req.trim();
+    if req.contains(',') {
+        return None;
+    }
+    let version_start = req.find(|c: char| c.is_ascii_digit())?;
+    // wildcards like `1.x`, but not an `x` in a pre-release or build tag
+    let core = req[version_start..].split(['-', '+']).next().unwrap_or_default();
+    if core.split('.').any(|part| matches!(part.trim(), "*" | "x" | "X")) {
+        return None;
+    }
+    let op = req[..version_start].trim();
+    if !matches!(op, "" | "^" | "=" | "~" | ">=") {
+        return None;
+    }
+    Some(format!("{op}{new_version}"))
+}
+
+#[test]
+fn version_bumps() {
+    let bump = |v: &str, b| Version::parse(v).unwrap().bump(&b).to_string();
+    assert_eq!(bump("1.2.3", VersionBump::Major), "2.0.0");
+    assert_eq!(bump("1.2.3+build", VersionBump::Minor), "1.3.0");
+    assert_eq!(bump("1.2.3", VersionBump::Patch), "1.2.4");
+    assert_eq!(bump("2.0.0-rc.1", VersionBump::Major), "2.0.0");
+    assert_eq!(bump("1.3.0-rc.1", VersionBump::Minor), "1.3.0");
+    assert_eq!(bump("1.3.1-rc.1", VersionBump::Minor), "1.4.0");
+    assert_eq!(bump("1.2.3", VersionBump::Prerelease("beta".into())), "1.2.4-beta.1");
+    assert_eq!(bump("1.2.4-beta.1", VersionBump::Prerelease("beta".into())), "1.2.4-beta.2");
+    assert_eq!(bump("1.2.4-beta", VersionBump::Prerelease("beta".into())), "1.2.4-beta.1");
+    assert_eq!(bump("1.2.4-alpha.3", VersionBump::Prerelease("beta".into())), "1.2.4-beta.1");
+    assert!(Version::parse("1.2").is_err());
+
+    assert_eq!(updated_requirement("1", "1.3.0").as_deref(), Some("1.3.0"));
+    assert_eq!(updated_requirement("=1.2.3", "1.3.0").as_deref(), Some("=1.3.0"));
+    assert_eq!(updated_requirement(">= 1.2", "1.3.0").as_deref(), Some(">=1.3.0"));
+    assert_eq!(updated_requirement("1.*", "1.3.0"), None);
+    assert_eq!(updated_requirement("1.X", "1.3.0"), None);
+    assert_eq!(updated_requirement("~1.0.0-xyz", "1.3.0").as_deref(), Some("~1.3.0"));
+    assert_eq!(updated_requirement("1.0.0+x86", "1.3.0").as_deref(), Some("1.3.0"));
+    assert_eq!(updated_requirement(">=1, <3", "1.3.0"), None);
+}
@@ -0,0 +1,128 @@
+//! JSON Schema of `Cargo.toml`. Most of it is derived from the structs, and only the types with custom parsing are described here.
+
+use crate::{CrateType, DebugSetting, Inheritable, Lint, LintSerdeParser, LtoSetting, Manifest, OptLevel, PanicStrategy, SplitDebuginfo, StripSetting};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{RootSchema, Schema};
+use schemars::JsonSchema;
+use serde_json::json;
+
+/// JSON Schema (draft 7) of `Cargo.toml` files, for editors and config validators.
+///
+/// Serialize it with `serde_json` to get the schema document. `metadata` tables and unknown keys accept any values.
+#[must_use]
+pub fn manifest_json_schema() -> RootSchema {
+    schemars::schema_for!(Manifest)
+}
+
+/// Any TOML value, for `toml::Value` fields
+pub(crate) struct AnyValue;
+
+impl JsonSchema for AnyValue {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "AnyValue".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        Schema::Bool(true)
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Inheritable<T> {
+    fn schema_name() -> String {
+        format!("Inheritable_for_{}", T::schema_name())
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        from_json(json!({
+            "anyOf": [
+                gen.subschema_for::<T>(),
+                {
+                    "description": "Inherited from `[workspace.package]` or `[workspace.dependencies]`",
+                    "type": "object",
+                    "properties": { "workspace": { "const": true } },
+                    "required": ["workspace"],
+                    "additionalProperties": false,
+                },
+            ],
+        }))
+    }
+}
+
+impl JsonSchema for Lint {
+    fn schema_name() -> String {
+        "Lint".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut schema = LintSerdeParser::json_schema(gen).into_object();
+        schema.metadata().description = Some("Lint level, or a table with `level` and `priority`".into());
+        schema.into()
+    }
+}
+
+/// Types that are parsed from `toml::Value` by hand
+macro_rules! schema {
+    ($($ty:ident => $schema:tt,)*) => {$(
+        impl JsonSchema for $ty {
+            fn schema_name() -> String {
+                stringify!($ty).into()
+            }
+
+            fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                from_json(json!($schema))
+            }
+        }
+    )*};
+}
+
+schema! {
+    DebugSetting => {
+        "description": "Verbosity of debug info",
+        "anyOf": [{ "type": "boolean" }, { "enum": [0, 1, 2] }, { "enum": ["none", "limited", "line-directives-only", "line-tables-only", "full"] }],
+    },
+    StripSetting => {
+        "description": "Handling of debug symbols",
+        "anyOf": [{ "type": "boolean" }, { "enum": ["none", "debuginfo", "symbols"] }],
+    },
+    LtoSetting => {
+        "description": "Handling of LTO",
+        "anyOf": [{ "type": "boolean" }, { "enum": ["off", "n", "no", "thin", "fat", "on", "y", "yes", "true", "false"] }],
+    },
+    OptLevel => {
+        "description": "Optimization level",
+        "enum": [0, 1, 2, 3, "0", "1", "2", "3", "s", "z"],
+    },
+    PanicStrategy => {
+        "description": "Handling of panics",
+        "enum": ["unwind", "abort", "immediate-abort"],
+    },
+    SplitDebuginfo => {
+        "description": "Handling of debug info files",
+        "enum": ["off", "packed", "unpacked"],
+    },
+    CrateType => {
+        "description": "Kind of artifact to build",
+        "type": "string",
+        "examples": ["bin", "lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro"],
+    },
+}
+
+fn from_json(schema: serde_json::Value) -> Schema {
+    serde_json::from_value(schema).expect("valid schema")
+}
+
+#[test]
+fn manifest_schema() {
+    let schema = serde_json::to_value(manifest_json_schema()).unwrap();
+    let defs = &schema["definitions"];
+    for name in ["Package", "Workspace", "DependencyDetail", "Profile", "Product", "Lint", "Inheritable_for_Edition", "OptionalFile", "Publish"] {
+        assert!(defs.get(name).is_some(), "{name} missing");
+    }
+    assert_eq!(defs["Package"]["properties"]["rust-version"]["anyOf"][0]["$ref"], "#/definitions/Inheritable_for_String");
+    assert_eq!(defs["Profile"]["properties"]["opt-level"]["anyOf"][0]["$ref"], "#/definitions/OptLevel");
+    assert!(defs["Package"]["properties"]["metadata"].get("type").is_none());
+}
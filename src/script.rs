@@ -0,0 +1,279 @@
+use crate::{Edition, Error, Manifest, OptionalFile, Product, Value};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Edition Cargo uses for scripts that don't specify one
+const SCRIPT_DEFAULT_EDITION: Edition = Edition::E2024;
+
+/// Parts of a single-file Cargo script (a `.rs` file with an embedded manifest).
+///
+/// ```text
+/// #!/usr/bin/env cargo
+/// ---cargo
+/// [dependencies]
+/// clap = "4"
+/// ---
+///
+/// fn main() {}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ScriptSource<'a> {
+    /// The `#!` line, including its line ending
+    pub shebang: Option<&'a str>,
+    /// The text after the opening `---` fence, usually `cargo`
+    pub info: Option<&'a str>,
+    /// The embedded manifest (without the fences)
+    pub frontmatter: Option<&'a str>,
+    /// The Rust code after the frontmatter
+    pub content: &'a str,
+}
+
+impl<'a> ScriptSource<'a> {
+    /// Split the script into the shebang, the frontmatter, and the code.
+    ///
+    /// Scripts without a frontmatter are fine, and have no manifest.
+    pub fn parse(source: &'a str) -> Result<Self, Error> {
+        let mut rest = source;
+        let mut shebang = None;
+        // `#![attr]` is Rust code, not a shebang
+        if rest.starts_with("#!") && !rest.starts_with("#![") {
+            let end = rest.find('\n').map_or(rest.len(), |n| n + 1);
+            shebang = Some(&rest[..end]);
+            rest = &rest[end..];
+        }
+
+        let mut no_frontmatter = Self { shebang, info: None, frontmatter: None, content: rest };
+
+        // Only blank lines may precede the frontmatter
+        let mut start = rest;
+        while let Some(line_end) = start.find('\n') {
+            if !start[..line_end].trim().is_empty() {
+                break;
+            }
+            start = &start[line_end + 1..];
+        }
+        let fence_len = start.bytes().take_while(|&b| b == b'-').count();
+        if fence_len < 3 {
+            no_frontmatter.content = rest;
+            return Ok(no_frontmatter);
+        }
+        let fence = &start[..fence_len];
+        let (info, body) = start[fence_len..].split_once('\n').ok_or(Error::Other("unclosed frontmatter in the script"))?;
+        let info = info.trim();
+
+        let mut pos = 0;
+        for line in body.split_inclusive('\n') {
+            if line.starts_with(fence) && line[fence_len..].trim().is_empty() {
+                return Ok(Self {
+                    shebang,
+                    info: Some(info).filter(|i| !i.is_empty()),
+                    frontmatter: Some(&body[..pos]),
+                    content: &body[pos + line.len()..],
+                });
+            }
+            if line.starts_with("---") {
+                return Err(Error::Other("frontmatter closing fence doesn't match the opening one"));
+            }
+            pos += line.len();
+        }
+        Err(Error::Other("unclosed frontmatter in the script"))
+    }
+}
+
+/// Replace (or add) the embedded manifest in a single-file Cargo script, keeping the rest of the script as-is.
+///
+/// `manifest_toml` is the new TOML text of the manifest. The implicit values (`name`, `edition`, `[[bin]]`)
+/// should not be added to it, so don't serialize a [`Manifest`] from [`Manifest::from_script`] without removing them.
+pub fn update_script_manifest(source: &str, manifest_toml: &str) -> Result<String, Error> {
+    let parsed = ScriptSource::parse(source)?;
+
+    // The fence must be longer than any dashes in the content
+    let fence_len = manifest_toml.lines()
+        .map(|l| l.bytes().take_while(|&b| b == b'-').count() + 1)
+        .max().unwrap_or(0)
+        .max(3);
+    let fence = "-".repeat(fence_len);
+
+    let mut out = String::with_capacity(source.len() + manifest_toml.len());
+    if let Some(shebang) = parsed.shebang {
+        out.push_str(shebang);
+        if !shebang.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out.push_str(&fence);
+    out.push_str(parsed.info.unwrap_or("cargo"));
+    out.push('\n');
+    out.push_str(manifest_toml);
+    if !manifest_toml.is_empty() && !manifest_toml.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&fence);
+    out.push('\n');
+    if parsed.frontmatter.is_none() && !parsed.content.starts_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(parsed.content);
+    Ok(out)
+}
+
+/// Rewrite the embedded manifest of the script file on disk. See [`update_script_manifest`].
+pub fn write_script_manifest(script_path: impl AsRef<Path>, manifest_toml: &str) -> Result<(), Error> {
+    let script_path = script_path.as_ref();
+    let source = fs::read_to_string(script_path)?;
+    fs::write(script_path, update_script_manifest(&source, manifest_toml)?)?;
+    Ok(())
+}
+
+impl Manifest<Value> {
+    /// Parse the manifest embedded in a single-file Cargo script (a `.rs` file with a `---cargo` frontmatter).
+    ///
+    /// It fills in the values Cargo implies for scripts: the package name (from the file name),
+    /// the edition (if not set), and a `[[bin]]` for the script itself. Scripts are not part of workspaces,
+    /// so workspace inheritance is not supported. Like Cargo, it returns an error if the frontmatter defines a workspace or products.
+    #[inline]
+    pub fn from_script(script_path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_script_with_metadata(script_path)
+    }
+
+    /// Parse the manifest embedded in a script's source code. It's **not** a file name, but the file's content.
+    ///
+    /// `script_path` is used for the package name and the path of the `[[bin]]`. It doesn't need to exist.
+    #[inline]
+    pub fn from_script_str(script_source: &str, script_path: &Path) -> Result<Self, Error> {
+        Self::from_script_str_with_metadata(script_source, script_path)
+    }
+}
+
+impl<Metadata: for<'a> Deserialize<'a>> Manifest<Metadata> {
+    /// [`Manifest::from_script`] with a custom type for `[package.metadata]`.
+    pub fn from_script_with_metadata(script_path: impl AsRef<Path>) -> Result<Self, Error> {
+        let script_path = script_path.as_ref();
+        let source = fs::read_to_string(script_path)?;
+        Self::from_script_str_with_metadata(&source, script_path)
+    }
+
+    /// [`Manifest::from_script_str`] with a custom type for `[package.metadata]`.
+    pub fn from_script_str_with_metadata(script_source: &str, script_path: &Path) -> Result<Self, Error> {
+        let parsed = ScriptSource::parse(script_source)?;
+        let mut toml: toml::Table = toml::from_str(parsed.frontmatter.unwrap_or(""))?;
+        if toml.contains_key("workspace") {
+            return Err(Error::Other("scripts can't define a workspace"));
+        }
+        // the script itself is the only product
+        if ["lib", "bin", "example", "test", "bench"].iter().any(|&k| toml.contains_key(k)) {
+            return Err(Error::Other("scripts can't define products like [lib] or [[bin]]"));
+        }
+
+        // Cargo allows the name to be missing, and has a different default edition for scripts
+        let file_stem = script_path.file_stem().and_then(|s| s.to_str()).ok_or(Error::Other("script file name is not valid UTF-8"))?;
+        let written = toml.clone();
+        let package = toml.entry("package").or_insert_with(|| Value::Table(Default::default()))
+            .as_table_mut().ok_or(Error::Other("package must be a table"))?;
+        package.entry("name").or_insert_with(|| Value::String(script_package_name(file_stem)));
+        package.entry("edition").or_insert_with(|| Value::String(SCRIPT_DEFAULT_EDITION.to_string()));
+
+        let mut manifest = Self::deserialize(Value::Table(toml))?;
+        manifest.set_explicit_keys(&written);
+        manifest.set_publish_default();
+
+        let Some(package) = &mut manifest.package else { return Err(Error::Other("not a package")) };
+        let edition = *package.edition.get().map_err(|_| Error::Other("scripts can't inherit from a workspace"))?;
+        package.build = Some(OptionalFile::Flag(false));
+        package.autolib = false;
+        package.autobins = false;
+        package.autoexamples = false;
+        package.autotests = false;
+        package.autobenches = false;
+
+        manifest.bin = vec![Product {
+            name: Some(package.name.clone()),
+            path: script_path.file_name().and_then(|p| p.to_str()).map(From::from),
+            edition: Some(edition),
+            ..Product::default()
+        }];
+        Ok(manifest)
+    }
+}
+
+const RESERVED_NAMES: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn", "else", "enum", "extern",
+    "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+    "build", "deps", "examples", "incremental", "test",
+];
+
+/// Compared case-insensitively
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "aux", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9", "con", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6",
+    "lpt7", "lpt8", "lpt9", "nul", "prn",
+];
+
+/// Like Cargo's `sanitize_package_name`, replaces characters that can't be in a package name
+fn script_package_name(file_stem: &str) -> String {
+    let placeholder = if file_stem.contains('_') { '_' } else { '-' };
+    // the name has to start like an identifier, so leading digits are dropped too
+    let mut chars = file_stem.chars().skip_while(|&c| !c.is_alphabetic() && c != '_');
+    let mut name: String = chars.next().into_iter()
+        .chain(chars.map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { placeholder }))
+        .collect();
+    if name.is_empty() {
+        name.push_str("package");
+    }
+    // names that clash with Rust keywords, Cargo's build directories, or Windows devices
+    while RESERVED_NAMES.contains(&name.as_str()) || WINDOWS_RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(&name)) {
+        name.push(placeholder);
+    }
+    name
+}
+
+#[test]
+fn script_parse() {
+    let src = "#!/usr/bin/env -S cargo +nightly -Zscript\n---cargo\n[dependencies]\nclap = \"4\"\n---\n\nfn main() {}\n";
+    let parsed = ScriptSource::parse(src).unwrap();
+    assert_eq!(parsed.shebang, Some("#!/usr/bin/env -S cargo +nightly -Zscript\n"));
+    assert_eq!(parsed.info, Some("cargo"));
+    assert_eq!(parsed.frontmatter, Some("[dependencies]\nclap = \"4\"\n"));
+    assert_eq!(parsed.content, "\nfn main() {}\n");
+
+    let m = Manifest::from_script_str(src, Path::new("tools/my script.rs")).unwrap();
+    let package = m.package();
+    assert_eq!(package.name, "my-script");
+    assert_eq!(package.edition(), SCRIPT_DEFAULT_EDITION);
+    assert!(!package.autobins);
+    assert_eq!(m.bin.len(), 1);
+    assert_eq!(m.bin[0].path.as_deref(), Some("my script.rs"));
+    assert_eq!(m.bin[0].name.as_deref(), Some("my-script"));
+    assert_eq!(m.dependencies["clap"].req(), "4");
+
+    let explicit = "----\n[package]\nedition = \"2021\"\n----\n#![allow(unused)]\nfn main() {}";
+    let m = Manifest::from_script_str(explicit, Path::new("test.rs")).unwrap();
+    assert_eq!(m.package().name, "test-");
+    assert_eq!(m.package().edition(), Edition::E2021);
+    assert_eq!(script_package_name("gen"), "gen-");
+    assert_eq!(script_package_name("1foo"), "foo");
+    assert_eq!(script_package_name("12_3"), "_3");
+    assert_eq!(script_package_name("!!!"), "package");
+    assert_eq!(script_package_name("42"), "package");
+    assert_eq!(script_package_name("CON"), "CON-");
+    assert_eq!(script_package_name("Nul_"), "Nul_");
+    assert_eq!(script_package_name("a.b"), "a-b");
+    for product in ["[lib]", "[[bin]]\nname = \"x\"", "[[example]]\nname = \"x\""] {
+        let src = format!("---\n{product}\n---\nfn main() {{}}\n");
+        assert!(Manifest::from_script_str(&src, Path::new("x.rs")).is_err(), "{product}");
+    }
+
+    let plain = "#![allow(unused)]\nfn main() {}\n";
+    assert_eq!(ScriptSource::parse(plain).unwrap().content, plain);
+    assert!(Manifest::from_script_str(plain, Path::new("x_y.rs")).unwrap().dependencies.is_empty());
+    assert!(ScriptSource::parse("---\n[package]\n").is_err());
+
+    let updated = update_script_manifest(src, "[dependencies]\nclap = \"5\"").unwrap();
+    assert_eq!(updated, "#!/usr/bin/env -S cargo +nightly -Zscript\n---cargo\n[dependencies]\nclap = \"5\"\n---\n\nfn main() {}\n");
+    let added = update_script_manifest(plain, "[dependencies]\n").unwrap();
+    assert_eq!(added, "---cargo\n[dependencies]\n---\n\n#![allow(unused)]\nfn main() {}\n");
+    assert_eq!(ScriptSource::parse(&added).unwrap().content, "\n#![allow(unused)]\nfn main() {}\n");
+}
@@ -0,0 +1,274 @@
+use crate::{Badges, Dependency, DepsSet, Error, LintGroups, Manifest, PackageTemplate, Value};
+use serde::de::{self, Deserialize, Visitor};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A key in `Cargo.toml` that isn't used by this crate, and most likely is a typo. See [`Manifest::from_str_strict`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct UnusedKey {
+    /// Keys of the tables leading to it, e.g. `["profile", "release", "opt_level"]`. Arrays of tables use indices, e.g. `["bin", "0", "nmae"]`.
+    pub path: Vec<String>,
+    /// A known key with a similar spelling
+    pub suggestion: Option<&'static str>,
+}
+
+impl fmt::Display for UnusedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unused manifest key: {}", self.path.join("."))?;
+        if let Some(s) = self.suggestion {
+            write!(f, " (did you mean `{s}`?)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Manifest<Value> {
+    /// Like [`Manifest::from_str`], but also returns keys that are not supported, and are most likely misspelled.
+    ///
+    /// The keys are from the `extra` fields of [`Manifest`], [`Package`](crate::Package), [`Workspace`](crate::Workspace), [`Profile`](crate::Profile), [`Product`](crate::Product), and [`Target`](crate::Target).
+    /// `[workspace.package]` and `[badges]` are checked against the source, because their structs don't keep unknown keys.
+    /// `package.metadata` and `workspace.metadata` can contain anything. Unknown keys in dependencies are kept in [`DependencyDetail::unstable`](crate::DependencyDetail::unstable),
+    /// and unknown keys of lints in [`Lint::config`](crate::Lint::config), so they're reported only if they're similar to a known key, like `defualt-features`.
+    pub fn from_str_strict(cargo_toml_content: &str) -> Result<(Self, Vec<UnusedKey>), Error> {
+        let raw: toml::Table = toml::from_str(cargo_toml_content)?;
+        let manifest = Self::from_str(cargo_toml_content)?;
+        let mut out = Vec::new();
+        let mut add = |prefix: &[&str], extra: &BTreeMap<String, Value>, known: &[&'static str]| {
+            for key in extra.keys() {
+                let path = prefix.iter().copied().chain([key.as_str()]).map(String::from).collect();
+                out.push(UnusedKey { path, suggestion: suggest(key, known) });
+            }
+        };
+
+        add(&[], &manifest.extra, MANIFEST_KEYS);
+        if let Some(package) = &manifest.package {
+            add(&["package"], &package.extra, PACKAGE_KEYS);
+        }
+        if let Some(ws) = &manifest.workspace {
+            add(&["workspace"], &ws.extra, WORKSPACE_KEYS);
+        }
+        let mut add_unknown = |prefix: &[&str], table: Option<&toml::Table>, known: &'static [&'static str]| {
+            let unknown = table.into_iter().flatten().filter(|(k, _)| !known.contains(&k.as_str())).map(|(k, v)| (k.clone(), v.clone())).collect();
+            add(prefix, &unknown, known);
+        };
+        add_unknown(&["workspace", "package"], raw.get("workspace").and_then(|w| w.get("package")).and_then(Value::as_table), fields_of::<PackageTemplate>());
+        add_unknown(&["badges"], raw.get("badges").and_then(Value::as_table), fields_of::<Badges>());
+
+        let p = &manifest.profile;
+        let profiles = [("release", &p.release), ("dev", &p.dev), ("test", &p.test), ("bench", &p.bench), ("doc", &p.doc)].into_iter()
+            .filter_map(|(name, p)| Some((name, p.as_ref()?)))
+            .chain(p.custom.iter().map(|(name, p)| (name.as_str(), p)));
+        for (name, profile) in profiles {
+            add(&["profile", name], &profile.extra, PROFILE_KEYS);
+            for (spec, o) in &profile.package {
+                add(&["profile", name, "package", spec], &o.extra, PROFILE_KEYS);
+            }
+            if let Some(o) = &profile.build_override {
+                add(&["profile", name, "build-override"], &o.extra, PROFILE_KEYS);
+            }
+        }
+
+        if let Some(lib) = &manifest.lib {
+            add(&["lib"], &lib.extra, PRODUCT_KEYS);
+        }
+        for (section, products) in [("bin", &manifest.bin), ("example", &manifest.example), ("test", &manifest.test), ("bench", &manifest.bench)] {
+            for (i, product) in products.iter().enumerate() {
+                add(&[section, &i.to_string()], &product.extra, PRODUCT_KEYS);
+            }
+        }
+        for (cfg, t) in &manifest.target {
+            add(&["target", cfg], &t.extra, TARGET_KEYS);
+        }
+
+        let mut check_deps = |prefix: &[&str], section: &str, deps: &DepsSet| {
+            for (name, dep) in deps {
+                let Dependency::Detailed(d) = dep else { continue };
+                for key in d.unstable.keys() {
+                    if let Some(suggestion) = suggest(key, DEPENDENCY_KEYS) {
+                        let path = prefix.iter().copied().chain([section, name, key]).map(String::from).collect();
+                        out.push(UnusedKey { path, suggestion: Some(suggestion) });
+                    }
+                }
+            }
+        };
+        for (section, deps) in [("dependencies", &manifest.dependencies), ("dev-dependencies", &manifest.dev_dependencies), ("build-dependencies", &manifest.build_dependencies)] {
+            check_deps(&[], section, deps);
+        }
+        for (cfg, t) in &manifest.target {
+            for (section, deps) in [("dependencies", &t.dependencies), ("dev-dependencies", &t.dev_dependencies), ("build-dependencies", &t.build_dependencies)] {
+                check_deps(&["target", cfg], section, deps);
+            }
+        }
+        if let Some(ws) = &manifest.workspace {
+            check_deps(&["workspace"], "dependencies", &ws.dependencies);
+        }
+        #[allow(deprecated)]
+        check_deps(&[], "replace", &manifest.replace);
+        for (registry, deps) in &manifest.patch {
+            check_deps(&["patch"], registry, deps);
+        }
+
+        let mut check_lints = |prefix: &[&str], groups: &LintGroups| {
+            for (group, lints) in groups {
+                for (name, lint) in lints {
+                    for key in lint.config.keys() {
+                        if let Some(suggestion) = suggest(key, LINT_KEYS) {
+                            let path = prefix.iter().copied().chain(["lints", group, name, key]).map(String::from).collect();
+                            out.push(UnusedKey { path, suggestion: Some(suggestion) });
+                        }
+                    }
+                }
+            }
+        };
+        if let Ok(lints) = manifest.lints.get() {
+            check_lints(&[], lints);
+        }
+        if let Some(ws) = &manifest.workspace {
+            check_lints(&["workspace"], &ws.lints);
+        }
+
+        out.sort_unstable();
+        Ok((manifest, out))
+    }
+}
+
+// serde doesn't list fields of structs with flattened maps, so these have to be kept in sync manually (checked by the `known_keys` test)
+pub(crate) const MANIFEST_KEYS: &[&str] = &["package", "workspace", "dependencies", "dev-dependencies", "build-dependencies", "target", "features", "replace", "patch", "lib", "profile", "badges", "bin", "bench", "test", "example", "lints"];
+pub(crate) const PACKAGE_KEYS: &[&str] = &["name", "version", "edition", "rust-version", "build", "workspace", "authors", "links", "description", "homepage", "documentation", "readme", "keywords", "categories", "exclude", "include", "license", "license-file", "repository", "default-run", "autobins", "autolib", "autoexamples", "autotests", "autobenches", "publish", "resolver", "metadata"];
+pub(crate) const WORKSPACE_KEYS: &[&str] = &["members", "default-members", "package", "exclude", "metadata", "resolver", "dependencies", "lints"];
+pub(crate) const PROFILE_KEYS: &[&str] = &["opt-level", "debug", "split-debuginfo", "rpath", "lto", "debug-assertions", "codegen-units", "panic", "incremental", "overflow-checks", "strip", "package", "build-override", "inherits"];
+pub(crate) const TARGET_KEYS: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+pub(crate) const PRODUCT_KEYS: &[&str] = &["path", "name", "test", "doctest", "bench", "doc", "plugin", "proc-macro", "harness", "edition", "crate-type", "required-features"];
+const DEPENDENCY_KEYS: &[&str] = &["version", "package", "registry", "registry-index", "path", "git", "branch", "tag", "rev", "features", "optional", "default-features", "workspace"];
+const LINT_KEYS: &[&str] = &["level", "priority"];
+
+/// Names of fields of a struct with derived `Deserialize`, which doesn't have flattened fields
+fn fields_of<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    struct FieldNames<'a>(&'a Cell<&'static [&'static str]>);
+
+    impl<'de> de::Deserializer<'de> for FieldNames<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(self, _: &'static str, fields: &'static [&'static str], _: V) -> Result<V::Value, Self::Error> {
+            self.0.set(fields);
+            Err(de::Error::custom("done"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let fields = Cell::new(&[][..]);
+    let _ = T::deserialize(FieldNames(&fields));
+    fields.get()
+}
+
+/// The most similar known key, e.g. `opt-level` for `opt_level`
+fn suggest(key: &str, known: &[&'static str]) -> Option<&'static str> {
+    let max = (key.len() / 3).max(1);
+    known.iter().copied()
+        .map(|k| (edit_distance(key, k), k))
+        .filter(|&(d, _)| d <= max)
+        .min()
+        .map(|(_, k)| k)
+}
+
+/// Levenshtein distance, with transposed letters counted as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[test]
+fn suggestions() {
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("nmae", "name"), 1);
+    assert_eq!(suggest("opt_level", PROFILE_KEYS), Some("opt-level"));
+    assert_eq!(suggest("defualt-features", DEPENDENCY_KEYS), Some("default-features"));
+    assert_eq!(suggest("dependancies", MANIFEST_KEYS), Some("dependencies"));
+    assert_eq!(suggest("something-else", MANIFEST_KEYS), None);
+}
+
+#[test]
+#[allow(deprecated)]
+fn known_keys() {
+    use crate::*;
+
+    // struct literals without `..Default::default()`, so that new fields have to be added here, and then to the lists
+    let keys = |value: Value, known: &[&str]| {
+        let mut keys: Vec<_> = value.as_table().unwrap().keys().cloned().collect();
+        let mut known: Vec<_> = known.iter().map(|k| k.to_string()).collect();
+        keys.sort();
+        known.sort();
+        assert_eq!(keys, known);
+    };
+    let deps = DepsSet::from([("dep".into(), Dependency::Simple("1".into()))]);
+    let lints = LintGroups::from([("rust".into(), LintSet::from([("unsafe_code".into(), Lint { level: LintLevel::Deny, priority: 0, config: BTreeMap::new() })]))]);
+    let product = Product {
+        path: Some("src/x.rs".into()), name: Some("x".into()), test: false, doctest: false, bench: false, doc: false, plugin: true, proc_macro: true, harness: false,
+        edition: Some(Edition::E2021), crate_type: vec![CrateType::Lib], required_features: vec!["f".into()],
+        extra: BTreeMap::new(), explicit: ExplicitKeys::default(),
+    };
+    let profile = Profile {
+        opt_level: Some(OptLevel::O3), debug: Some(DebugSetting::Full), split_debuginfo: Some(SplitDebuginfo::Packed), rpath: Some(true), lto: Some(LtoSetting::Fat),
+        debug_assertions: Some(true), codegen_units: Some(1), panic: Some(PanicStrategy::Abort), incremental: Some(true), overflow_checks: Some(true), strip: Some(StripSetting::Symbols),
+        package: BTreeMap::from([("p".into(), ProfileOverride::default())]), build_override: Some(ProfileOverride::default()), inherits: Some("release".into()),
+        extra: BTreeMap::new(), explicit: ExplicitKeys::default(),
+    };
+    let target = Target { dependencies: deps.clone(), dev_dependencies: deps.clone(), build_dependencies: deps.clone(), extra: BTreeMap::new(), explicit: ExplicitKeys::default() };
+    let workspace = Workspace {
+        members: vec!["a".into()], default_members: vec!["a".into()], package: Some(PackageTemplate::default()), exclude: vec!["b".into()], metadata: Some(Value::Boolean(true)),
+        resolver: Some(Resolver::V2), dependencies: deps.clone(), lints: lints.clone(),
+        extra: BTreeMap::new(), explicit: ExplicitKeys::default(),
+    };
+    let text = || Some(Inheritable::Set("x".to_string()));
+    let list = || Inheritable::Set(vec!["x".to_string()]);
+    let package = Package {
+        name: "p".into(), version: Inheritable::Set("1.0.0".into()), edition: Inheritable::Set(Edition::E2021), rust_version: text(), build: Some(OptionalFile::Flag(false)),
+        workspace: Some("..".into()), authors: list(), links: Some("x".into()), description: text(), homepage: text(), documentation: text(),
+        readme: Inheritable::Set(OptionalFile::Flag(false)), keywords: list(), categories: list(), exclude: list(), include: list(), license: text(),
+        license_file: Some(Inheritable::Set("LICENSE".into())), repository: text(), default_run: Some("x".into()),
+        autobins: false, autolib: false, autoexamples: false, autotests: false, autobenches: false,
+        publish: Inheritable::Set(Publish::Flag(false)), resolver: Some(Resolver::V2), metadata: Some(Value::Boolean(true)),
+        extra: BTreeMap::new(), explicit: ExplicitKeys::default(),
+    };
+    let manifest = Manifest {
+        package: Some(package.clone()), workspace: Some(workspace.clone()), dependencies: deps.clone(), dev_dependencies: deps.clone(), build_dependencies: deps.clone(),
+        target: BTreeMap::from([("cfg(unix)".into(), target.clone())]), features: BTreeMap::from([("f".into(), vec![])]), replace: deps.clone(),
+        patch: BTreeMap::from([("crates-io".into(), deps.clone())]), lib: Some(product.clone()),
+        profile: Profiles { release: Some(profile.clone()), ..Profiles::default() },
+        badges: Badges { maintenance: Maintenance { status: MaintenanceStatus::ActivelyDeveloped }, ..Badges::default() },
+        bin: vec![product.clone()], bench: vec![product.clone()], test: vec![product.clone()], example: vec![product.clone()], lints: Inheritable::Set(lints),
+        provenance: Provenance::default(), extra: BTreeMap::new(), explicit: ExplicitKeys::default(),
+    };
+    keys(Value::try_from(manifest).unwrap(), MANIFEST_KEYS);
+    keys(Value::try_from(package).unwrap(), PACKAGE_KEYS);
+    keys(Value::try_from(workspace).unwrap(), WORKSPACE_KEYS);
+    keys(Value::try_from(profile).unwrap(), PROFILE_KEYS);
+    keys(Value::try_from(target).unwrap(), TARGET_KEYS);
+    keys(Value::try_from(product).unwrap(), PRODUCT_KEYS);
+    assert!(fields_of::<Badges>().contains(&"maintenance"));
+    assert!(fields_of::<PackageTemplate>().contains(&"rust-version"));
+}
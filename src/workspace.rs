@@ -0,0 +1,290 @@
+use crate::{Dependency, DepsSet, Error, Kind, Manifest, Value};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// A package that is a member of a workspace. See [`Manifest::load_workspace_members`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct WorkspaceMember<Metadata = Value> {
+    /// Directory of the member's `Cargo.toml`. It's the workspace root dir joined with the path from `members`.
+    pub dir: PathBuf,
+    /// Parsed and completed with inheritance from the workspace
+    pub manifest: Manifest<Metadata>,
+}
+
+impl<Metadata> WorkspaceMember<Metadata> {
+    /// Package name
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &str {
+        self.manifest.package.as_ref().map_or("", |p| p.name())
+    }
+}
+
+impl<Ignored> Manifest<Ignored> {
+    /// Find and load all packages in this workspace, including the root package if there is one.
+    ///
+    /// `self` must be the root workspace manifest, and `workspace_dir` the directory it's in.
+    /// Paths in `workspace.members` can use `*` and `?` wildcards. Members' inheritance from the workspace is applied.
+    #[inline]
+    pub fn load_workspace_members(&self, workspace_dir: &Path) -> Result<Vec<WorkspaceMember<Value>>, Error> {
+        self.load_workspace_members_with_metadata(workspace_dir)
+    }
+
+    /// [`Manifest::load_workspace_members`] with a custom type for members' `[package.metadata]`.
+    pub fn load_workspace_members_with_metadata<Metadata: for<'a> Deserialize<'a>>(&self, workspace_dir: &Path) -> Result<Vec<WorkspaceMember<Metadata>>, Error> {
+        self.workspace_member_dirs(workspace_dir)?.into_iter().map(|dir| {
+            let manifest_path = dir.join("Cargo.toml");
+            let load = || {
+                let mut manifest = Manifest::from_slice_with_metadata(&fs::read(&manifest_path)?)?;
+                manifest.complete_from_path_and_workspace(&manifest_path, Some((self, workspace_dir)))?;
+                Ok(manifest)
+            };
+            match load() {
+                Ok(manifest) => Ok(WorkspaceMember { dir, manifest }),
+                Err(e) => Err(Error::WorkspaceMember(Box::new((e, dir)))),
+            }
+        }).collect()
+    }
+
+    /// Directories of all members, including the root if it's a package
+    pub(crate) fn workspace_member_dirs(&self, workspace_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        let ws = self.workspace.as_ref().ok_or(Error::Other("not a workspace"))?;
+        let mut dirs = BTreeSet::new();
+        if self.package.is_some() {
+            dirs.insert(PathBuf::new());
+        }
+        for pattern in &ws.members {
+            expand_member_pattern(workspace_dir, pattern, &mut dirs)?;
+        }
+        let exclude: Vec<_> = ws.exclude.iter().map(|e| normalize(Path::new(e))).collect();
+        dirs.retain(|d| !exclude.iter().any(|e| d.starts_with(e)));
+        Ok(dirs.into_iter().map(|d| workspace_dir.join(d)).collect())
+    }
+}
+
+/// Adds relative paths of directories
+fn expand_member_pattern(workspace_dir: &Path, pattern: &str, out: &mut BTreeSet<PathBuf>) -> Result<(), Error> {
+    let pattern = normalize(Path::new(pattern));
+    let mut dirs = vec![PathBuf::new()];
+    let mut is_glob = false;
+    for component in pattern.iter() {
+        let component = component.to_str().ok_or(Error::Other("workspace member path is not valid UTF-8"))?;
+        if !component.contains(['*', '?']) {
+            dirs.iter_mut().for_each(|d| d.push(component));
+            continue;
+        }
+        is_glob = true;
+        let mut matches = Vec::new();
+        for dir in dirs {
+            let Ok(entries) = fs::read_dir(workspace_dir.join(&dir)) else { continue };
+            for e in entries {
+                let e = e?;
+                if e.file_type()?.is_dir() && e.file_name().to_str().is_some_and(|name| wildcard_match(component, name)) {
+                    matches.push(dir.join(e.file_name()));
+                }
+            }
+        }
+        dirs = matches;
+    }
+    // Cargo skips non-package directories matched by a glob
+    out.extend(dirs.into_iter().filter(|d| !is_glob || workspace_dir.join(d).join("Cargo.toml").exists()));
+    Ok(())
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.chars().next() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).filter(|&i| name.is_char_boundary(i)).any(|i| wildcard_match(&pattern[1..], &name[i..])),
+        Some(p) => name.chars().next().is_some_and(|n| p == '?' || p == n) && {
+            let n_len = name.chars().next().map_or(0, char::len_utf8);
+            wildcard_match(&pattern[p.len_utf8()..], &name[n_len..])
+        },
+    }
+}
+
+/// Removes `.` and `..` without touching the filesystem
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for c in path.components() {
+        match c {
+            Component::CurDir => {},
+            Component::ParentDir if matches!(out.components().next_back(), Some(Component::Normal(_))) => { out.pop(); },
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// How workspace members depend on each other through `path` dependencies. See [`WorkspaceGraph::new`].
+#[derive(Debug, Clone)]
+pub struct WorkspaceGraph<'a, Metadata = Value> {
+    members: BTreeMap<&'a str, &'a WorkspaceMember<Metadata>>,
+    edges: Vec<WorkspaceEdge<'a>>,
+}
+
+/// A dependency of one workspace member on another
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct WorkspaceEdge<'a> {
+    /// Package name of the member that has the dependency
+    pub from: &'a str,
+    /// Package name of the member it depends on
+    pub to: &'a str,
+    /// Dependency's key in the `[dependencies]` table (may be renamed)
+    pub key: &'a str,
+    /// Which `[dependencies]` section it's in
+    pub kind: Kind,
+    /// `[target."cfg".dependencies]`
+    pub target: Option<&'a str>,
+    /// Enabled only by a feature
+    pub optional: bool,
+}
+
+impl<'a, Metadata> WorkspaceGraph<'a, Metadata> {
+    /// Find dependencies between the members, as loaded by [`Manifest::load_workspace_members`].
+    ///
+    /// Dependencies are matched by their `path`. Dependencies that still use `{ workspace = true }`, because inheritance hasn't been applied, are matched by package name.
+    #[must_use]
+    pub fn new(members: &'a [WorkspaceMember<Metadata>]) -> Self {
+        let by_dir: BTreeMap<_, _> = members.iter().map(|m| (normalize(&m.dir), m.name())).collect();
+        let mut edges = Vec::new();
+        for member in members {
+            let m = &member.manifest;
+            let sections = [(&m.dependencies, Kind::Normal, None), (&m.build_dependencies, Kind::Build, None), (&m.dev_dependencies, Kind::Dev, None)].into_iter()
+                .chain(m.target.iter().flat_map(|(cfg, t)| [
+                    (&t.dependencies, Kind::Normal, Some(cfg.as_str())),
+                    (&t.build_dependencies, Kind::Build, Some(cfg.as_str())),
+                    (&t.dev_dependencies, Kind::Dev, Some(cfg.as_str())),
+                ]));
+            for (deps, kind, target) in sections {
+                Self::add_edges(&mut edges, member, deps, kind, target, &by_dir);
+            }
+        }
+        edges.sort_unstable();
+        Self {
+            members: members.iter().map(|m| (m.name(), m)).collect(),
+            edges,
+        }
+    }
+
+    fn add_edges(edges: &mut Vec<WorkspaceEdge<'a>>, member: &'a WorkspaceMember<Metadata>, deps: &'a DepsSet, kind: Kind, target: Option<&'a str>, by_dir: &BTreeMap<PathBuf, &'a str>) {
+        for (key, dep) in deps {
+            let to = match dep {
+                Dependency::Detailed(d) => {
+                    let Some(path) = &d.path else { continue };
+                    // inherited paths have been made relative to the workspace root already
+                    let dir = if d.inherited { normalize(Path::new(path)) } else { normalize(&member.dir.join(path)) };
+                    by_dir.get(&dir).copied()
+                },
+                Dependency::Inherited(_) => by_dir.values().copied().find(|&name| name == key),
+                Dependency::Simple(_) => None,
+            };
+            if let Some(to) = to {
+                edges.push(WorkspaceEdge { from: member.name(), to, key, kind, target, optional: dep.optional() });
+            }
+        }
+    }
+
+    /// Members by package name
+    #[must_use]
+    pub fn members(&self) -> &BTreeMap<&'a str, &'a WorkspaceMember<Metadata>> {
+        &self.members
+    }
+
+    /// All dependencies between the members, sorted
+    #[must_use]
+    pub fn edges(&self) -> &[WorkspaceEdge<'a>] {
+        &self.edges
+    }
+
+    /// Members that this member depends on
+    pub fn dependencies_of<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s WorkspaceEdge<'a>> + 's {
+        self.edges.iter().filter(move |e| e.from == name)
+    }
+
+    /// Reverse dependencies: members that depend on this member
+    pub fn dependents_of<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s WorkspaceEdge<'a>> + 's {
+        self.edges.iter().filter(move |e| e.to == name)
+    }
+
+    /// Members that need to be rebuilt or retested after a change to this member, including itself.
+    ///
+    /// It follows reverse dependencies transitively, including dev-dependencies. Returns an empty set if there's no such member.
+    #[must_use]
+    pub fn affected_by(&self, name: &str) -> BTreeSet<&'a str> {
+        let mut affected = BTreeSet::new();
+        let Some((&name, _)) = self.members.get_key_value(name) else { return affected };
+        let mut queue = VecDeque::from([name]);
+        while let Some(name) = queue.pop_front() {
+            if affected.insert(name) {
+                queue.extend(self.dependents_of(name).map(|e| e.from));
+            }
+        }
+        affected
+    }
+
+    /// Groups of members that depend on each other in a loop, excluding dev-dependencies (which are allowed to form cycles).
+    #[must_use]
+    pub fn cycles(&self) -> Vec<Vec<&'a str>> {
+        let mut remaining: BTreeSet<_> = self.members.keys().copied().collect();
+        let mut out = Vec::new();
+        // strongly connected components, by intersecting forward and backward reachability
+        while let Some(&start) = remaining.iter().next() {
+            let forward = self.reachable(start, |e| (e.from, e.to));
+            let backward = self.reachable(start, |e| (e.to, e.from));
+            let group: Vec<_> = forward.intersection(&backward).copied().collect();
+            let self_loop = self.edges.iter().any(|e| e.kind != Kind::Dev && e.from == start && e.to == start);
+            for m in &group {
+                remaining.remove(m);
+            }
+            if group.len() > 1 || self_loop {
+                out.push(group);
+            }
+        }
+        out
+    }
+
+    fn reachable(&self, start: &'a str, direction: impl Fn(&WorkspaceEdge<'a>) -> (&'a str, &'a str)) -> BTreeSet<&'a str> {
+        let mut seen = BTreeSet::new();
+        let mut queue = vec![start];
+        while let Some(name) = queue.pop() {
+            if seen.insert(name) {
+                queue.extend(self.edges.iter().filter(|e| e.kind != Kind::Dev).map(&direction).filter(|&(from, _)| from == name).map(|(_, to)| to));
+            }
+        }
+        seen
+    }
+
+    /// Member names ordered so that every member comes after the members it depends on. Dev-dependencies are ignored.
+    ///
+    /// Fails if there are [`cycles`](Self::cycles).
+    pub fn topological_order(&self) -> Result<Vec<&'a str>, Error> {
+        let mut num_deps: BTreeMap<&str, usize> = self.members.keys().map(|&m| (m, 0)).collect();
+        let build_edges = || self.edges.iter().filter(|e| e.kind != Kind::Dev);
+        // the same pair of members may be connected by multiple edges
+        let unique: BTreeSet<_> = build_edges().map(|e| (e.from, e.to)).collect();
+        for &(from, _) in &unique {
+            *num_deps.entry(from).or_default() += 1;
+        }
+        let mut ready: BTreeSet<_> = num_deps.iter().filter(|&(_, &n)| n == 0).map(|(&m, _)| m).collect();
+        let mut out = Vec::with_capacity(num_deps.len());
+        while let Some(name) = ready.pop_first() {
+            out.push(name);
+            for &(from, _) in unique.iter().filter(|&&(_, to)| to == name) {
+                let n = num_deps.get_mut(from).unwrap();
+                *n -= 1;
+                if *n == 0 {
+                    ready.insert(from);
+                }
+            }
+        }
+        if out.len() != num_deps.len() {
+            let cycles = self.cycles().iter().map(|c| c.join(" → ")).collect::<Vec<_>>().join("; ");
+            return Err(Error::WorkspaceIntegrity(format!("workspace members depend on each other in a cycle: {cycles}")));
+        }
+        Ok(out)
+    }
+}
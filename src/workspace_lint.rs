@@ -0,0 +1,115 @@
+use crate::workspace::normalize;
+use crate::{Dependency, Error, Manifest, Value};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A problem with workspace inheritance found by [`Manifest::lint_workspace`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum WorkspaceLint {
+    /// A member's `[package]` field has the same value as `[workspace.package]`, and could use `workspace = true` instead
+    DuplicatesWorkspaceValue { member: String, field: String },
+    /// A member's `[package]` field uses `workspace = true`, but `[workspace.package]` doesn't have it
+    MissingFromWorkspace { member: String, field: String },
+    /// A member's dependency uses `workspace = true`, but `[workspace.dependencies]` doesn't have it
+    MissingWorkspaceDependency { member: String, dependency: String },
+    /// `optional = true` is not allowed in `[workspace.dependencies]`. Members have to set it.
+    OptionalWorkspaceDependency { dependency: String },
+    /// No member uses this entry of `[workspace.dependencies]`
+    UnusedWorkspaceDependency { dependency: String },
+}
+
+impl WorkspaceLint {
+    /// Cargo refuses to build the workspace with this problem
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::MissingFromWorkspace { .. } | Self::MissingWorkspaceDependency { .. } | Self::OptionalWorkspaceDependency { .. })
+    }
+}
+
+impl fmt::Display for WorkspaceLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicatesWorkspaceValue { member, field } => write!(f, "`{member}` has the same `{field}` as the workspace; use `{field}.workspace = true`"),
+            Self::MissingFromWorkspace { member, field } => write!(f, "`{member}` inherits `{field}`, but it's not in [workspace.package]"),
+            Self::MissingWorkspaceDependency { member, dependency } => write!(f, "`{member}` inherits dependency `{dependency}`, but it's not in [workspace.dependencies]"),
+            Self::OptionalWorkspaceDependency { dependency } => write!(f, "workspace dependency `{dependency}` can't be optional; set `optional = true` in members instead"),
+            Self::UnusedWorkspaceDependency { dependency } => write!(f, "workspace dependency `{dependency}` is not used by any member"),
+        }
+    }
+}
+
+impl<Ignored> Manifest<Ignored> {
+    /// Check all members of this workspace for problems with inheritance from the workspace.
+    ///
+    /// `self` must be the root workspace manifest, and `workspace_dir` the directory it's in.
+    /// Members are read from disk without applying inheritance, so this works even if they fail to load with [`Error::WorkspaceIntegrity`].
+    pub fn lint_workspace(&self, workspace_dir: &Path) -> Result<Vec<WorkspaceLint>, Error> {
+        let ws = self.workspace.as_ref().ok_or(Error::Other("not a workspace"))?;
+        let template = ws.package.as_ref().map(Value::try_from).transpose().map_err(|_| Error::Other("can't serialize workspace.package"))?;
+        let template = template.as_ref().and_then(Value::as_table);
+        let inherited = {
+            let mut t = toml::Table::new();
+            t.insert("workspace".into(), Value::Boolean(true));
+            Value::Table(t)
+        };
+
+        let mut out = Vec::new();
+        let mut used_deps = BTreeSet::new();
+        for dir in self.workspace_member_dirs(workspace_dir)? {
+            let member: Manifest = Manifest::from_slice(&fs::read(dir.join("Cargo.toml"))?)
+                .map_err(|e| Error::WorkspaceMember(Box::new((e, dir.clone()))))?;
+            let Some(package) = &member.package else { continue };
+            let name = package.name();
+
+            let fields = Value::try_from(package).map_err(|_| Error::Other("can't serialize package"))?;
+            // fields filled in with defaults, like `edition = "2015"`, haven't been written by the member
+            for (field, value) in fields.as_table().into_iter().flatten().filter(|(field, _)| package.explicit.contains(field)) {
+                let template_value = template.and_then(|t| t.get(field));
+                if *value == inherited {
+                    // Cargo fails to inherit keys that aren't in `[workspace.package]`
+                    if template_value.is_none() {
+                        out.push(WorkspaceLint::MissingFromWorkspace { member: name.into(), field: field.clone() });
+                    }
+                    continue;
+                }
+                let Some(template_value) = template_value else { continue };
+                let same = if field == "readme" || field == "license-file" {
+                    let resolve = |dir: &Path, v: &Value| v.as_str().map(|p| normalize(&dir.join(p)));
+                    resolve(&dir, value).is_some() && resolve(&dir, value) == resolve(workspace_dir, template_value)
+                } else {
+                    value == template_value
+                };
+                if same {
+                    out.push(WorkspaceLint::DuplicatesWorkspaceValue { member: name.into(), field: field.clone() });
+                }
+            }
+
+            let all_deps = [&member.dependencies, &member.build_dependencies, &member.dev_dependencies].into_iter()
+                .chain(member.target.values().flat_map(|t| [&t.dependencies, &t.build_dependencies, &t.dev_dependencies]));
+            for (key, dep) in all_deps.flatten() {
+                if let Dependency::Inherited(_) = dep {
+                    if ws.dependencies.contains_key(key) {
+                        used_deps.insert(key.clone());
+                    } else {
+                        out.push(WorkspaceLint::MissingWorkspaceDependency { member: name.into(), dependency: key.clone() });
+                    }
+                }
+            }
+        }
+
+        for (key, dep) in &ws.dependencies {
+            if dep.optional() {
+                out.push(WorkspaceLint::OptionalWorkspaceDependency { dependency: key.clone() });
+            }
+            if !used_deps.contains(key) {
+                out.push(WorkspaceLint::UnusedWorkspaceDependency { dependency: key.clone() });
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+        Ok(out)
+    }
+}
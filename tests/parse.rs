@@ -1,4 +1,4 @@
-use cargo_toml::{Edition, LintLevel, Manifest, StripSetting};
+use cargo_toml::{CrateType, Deprecation, Edition, LintLevel, Manifest, OptLevel, StripSetting};
 use std::fs::read;
 use std::path::Path;
 
@@ -27,7 +27,7 @@ fn opt_level() {
     let m = Manifest::from_slice(&read("tests/opt_level.toml").unwrap()).unwrap();
     let package = m.package();
     assert_eq!("byteorder", package.name);
-    assert_eq!(3, m.profile.bench.as_ref().unwrap().opt_level.as_ref().unwrap().as_integer().unwrap());
+    assert_eq!(Some(OptLevel::O3), m.profile.bench.as_ref().unwrap().opt_level);
     assert!(!m.lib.as_ref().unwrap().bench);
     assert_eq!(cargo_toml::Edition::E2015, package.edition());
     assert_eq!(1, m.patch.len());
@@ -80,7 +80,7 @@ fn autolib() {
     assert!(!package.autoexamples);
     let lib = m.lib.unwrap();
     assert_eq!("auto_lib", lib.name.unwrap());
-    assert_eq!(lib.crate_type, vec!["lib".to_string()]);
+    assert_eq!(lib.crate_type, [CrateType::Lib]);
     assert_eq!(0, m.bin.len());
     assert_eq!(Some(StripSetting::None), m.profile.release.unwrap().strip);
     #[allow(deprecated)]
@@ -100,7 +100,7 @@ fn autolib2() {
     let lib = m.lib.unwrap();
     assert_eq!("auto_lib2", lib.name.unwrap());
     assert_eq!(cargo_toml::Edition::E2018, lib.edition.unwrap());
-    assert_eq!(lib.crate_type, vec!["lib".to_string()]);
+    assert_eq!(lib.crate_type, [CrateType::Lib]);
     assert_eq!(0, m.bin.len());
 }
 
@@ -116,7 +116,7 @@ fn autolib3() {
     let lib = m.lib.unwrap();
     assert_eq!("renamed_lib", lib.name.unwrap());
     assert_eq!(cargo_toml::Edition::E2021, lib.edition.unwrap());
-    assert_eq!(lib.crate_type, vec!["lib".to_string()]);
+    assert_eq!(lib.crate_type, [CrateType::Lib]);
     assert_eq!(0, m.bin.len());
 }
 
@@ -222,6 +222,61 @@ fn inherit() {
     assert!(!path_dep.inherited);
 }
 
+#[test]
+fn provenance() {
+    use cargo_toml::{Kind, ValueSource};
+
+    let m = Manifest::from_path("tests/inheritance/hi/Cargo.toml").unwrap();
+    let p = &m.provenance;
+    let root = Path::new("tests/inheritance");
+    assert_eq!(p.workspace_root.as_deref(), Some(root));
+    assert_eq!(p.package_fields.iter().map(|s| s.as_str()).collect::<Vec<_>>(), ["exclude", "license-file", "readme"]);
+    assert_eq!(p.package_field("license-file"), ValueSource::WorkspacePackage(root));
+    assert_eq!(p.package_field("version"), ValueSource::Local);
+    // Simple dependencies don't have the `inherited` flag
+    assert!(matches!(m.dependencies["testdep"], cargo_toml::Dependency::Simple(_)));
+    assert_eq!(p.dependency(Kind::Normal, None, "testdep"), ValueSource::WorkspaceDependencies(root));
+    assert_eq!(p.dependency(Kind::Normal, None, "normal"), ValueSource::Local);
+    assert_eq!(p.dependency(Kind::Dev, None, "testdep"), ValueSource::Local);
+    assert_eq!(p.lints(), ValueSource::Local);
+    let manifest_path = Path::new("tests/inheritance/hi/Cargo.toml");
+    assert_eq!(p.package_field("readme").manifest_path(manifest_path), Path::new("tests/inheritance/Cargo.toml"));
+    assert_eq!(p.package_field("name").manifest_path(manifest_path), manifest_path);
+
+    assert!(Manifest::from_path("tests/autobin/Cargo.toml").unwrap().provenance.is_empty());
+    // it's not a part of the manifest's data
+    let mut without = m.clone();
+    without.provenance = Default::default();
+    assert_eq!(m, without);
+}
+
+#[test]
+fn restore_workspace_inheritance() {
+    use cargo_toml::{Dependency, Inheritable};
+
+    let original = Manifest::from_slice(&read("tests/inheritance/hi/Cargo.toml").unwrap()).unwrap();
+    let mut m = Manifest::from_path("tests/inheritance/hi/Cargo.toml").unwrap();
+    assert!(!m.needs_workspace_inheritance());
+    m.package.as_mut().unwrap().exclude = Inheritable::Set(vec!["changed".into()]);
+    m.dependencies.get_mut("testdep").unwrap().detail_mut().version = Some("2".into());
+    m.restore_workspace_inheritance();
+
+    assert!(m.provenance.is_empty());
+    let package = m.package();
+    assert!(!package.readme.is_set());
+    assert!(package.license_file.as_ref().is_some_and(|f| !f.is_set()));
+    assert_eq!(package.exclude(), ["changed"]);
+    assert_eq!(m.dependencies["otherdep"], original.dependencies["otherdep"]);
+    assert_eq!(m.dependencies["doubly_nested"], original.dependencies["doubly_nested"]);
+    assert!(matches!(&m.dependencies["testdep"], Dependency::Detailed(d) if d.version.as_deref() == Some("2")));
+
+    let serialized = toml::to_string(&m).unwrap();
+    assert!(serialized.contains("[package.readme]\nworkspace = true"), "{serialized}");
+    assert!(serialized.contains("[dependencies.otherdep]\nfeatures = [\"bar\"]\nworkspace = true"), "{serialized}");
+    let reparsed = Manifest::from_str(&serialized).unwrap();
+    assert!(reparsed.needs_workspace_inheritance());
+}
+
 #[test]
 fn inherit_doubly_nested() {
     let manifest = Manifest::from_path("tests/inheritance/hi/doubly_nested/Cargo.toml").unwrap();
@@ -312,3 +367,679 @@ fn lints_ws() {
     let ws_lints = &ws.workspace.as_ref().unwrap().lints["rust"];
     assert!(ws_lints.contains_key("a_ws_lint"), "{ws_lints:?}");
 }
+
+#[test]
+fn workspace_graph() {
+    use cargo_toml::{Kind, WorkspaceGraph};
+
+    let root = Path::new("tests/workspace_graph");
+    let ws = Manifest::from_path(root.join("Cargo.toml")).unwrap();
+    let members = ws.load_workspace_members(root).unwrap();
+    let names: Vec<_> = members.iter().map(|m| m.name()).collect();
+    assert_eq!(names, ["app", "core", "net", "testkit", "cli"]);
+
+    let graph = WorkspaceGraph::new(&members);
+    assert_eq!(graph.edges().len(), 6);
+    let net: Vec<_> = graph.dependencies_of("net").collect();
+    assert_eq!(net.len(), 2);
+    assert_eq!((net[0].to, net[0].kind, net[0].target, net[0].optional), ("core", Kind::Normal, Some("cfg(unix)"), true));
+    assert_eq!((net[1].to, net[1].kind, net[1].target, net[1].optional), ("testkit", Kind::Build, None, false));
+    assert_eq!(graph.dependents_of("core").map(|e| e.from).collect::<Vec<_>>(), ["net", "testkit"]);
+
+    // core <-> testkit is only a dev-dependency cycle
+    assert!(graph.cycles().is_empty());
+    assert_eq!(graph.topological_order().unwrap(), ["core", "testkit", "net", "cli", "app"]);
+    assert_eq!(graph.affected_by("testkit").into_iter().collect::<Vec<_>>(), ["app", "cli", "core", "net", "testkit"]);
+    assert_eq!(graph.affected_by("cli").into_iter().collect::<Vec<_>>(), ["app", "cli"]);
+    assert!(graph.affected_by("ignored").is_empty());
+
+    let mut cyclic = members.clone();
+    let core = cyclic.iter_mut().find(|m| m.name() == "core").unwrap();
+    core.manifest.dependencies = core.manifest.dev_dependencies.clone();
+    let graph = WorkspaceGraph::new(&cyclic);
+    assert_eq!(graph.cycles(), [["core", "testkit"]]);
+    assert!(graph.topological_order().is_err());
+}
+
+#[test]
+fn release_plan() {
+    use cargo_toml::{ReleasePlan, VersionBump};
+
+    let root = Path::new("tests/workspace_graph");
+    let members = Manifest::from_path(root.join("Cargo.toml")).unwrap().load_workspace_members(root).unwrap();
+
+    let plan = ReleasePlan::version_bump(root, &members, "core", &VersionBump::Minor).unwrap();
+    assert_eq!(plan.packages, [("core".to_string(), "1.0.0".to_string(), "1.1.0".to_string())]);
+    let edits: Vec<_> = plan.edits.iter().map(|e| e.to_string()).collect();
+    assert_eq!(edits, [
+        "tests/workspace_graph/crates/core/Cargo.toml: package.version = \"1.0.0\" → \"1.1.0\"",
+        "tests/workspace_graph/Cargo.toml: workspace.dependencies.core.version = \"1\" → \"1.1.0\"",
+    ]);
+
+    let plan = ReleasePlan::version_bump(root, &members, "net", &VersionBump::Prerelease("rc".into())).unwrap();
+    assert_eq!(plan.edits.len(), 2);
+    assert_eq!(plan.edits[1].toml_path, ["dependencies", "net", "version"]);
+    assert_eq!(plan.edits[1].new, "=1.0.1-rc.1");
+
+    // cli and app inherit the version
+    let plan = ReleasePlan::version_bump(root, &members, "cli", &VersionBump::Patch).unwrap();
+    let packages: Vec<_> = plan.packages.iter().map(|(name, _, new)| (name.as_str(), new.as_str())).collect();
+    assert_eq!(packages, [("app", "0.5.1"), ("cli", "0.5.1")]);
+    assert_eq!(plan.edits.len(), 2);
+    assert_eq!(plan.edits[0].toml_path, ["workspace", "package", "version"]);
+    assert_eq!(plan.edits[1].toml_path, ["dependencies", "cli", "version"]);
+
+    #[cfg(feature = "edit")]
+    {
+        let path = root.join("Cargo.toml");
+        let updated = plan.apply_to_toml(&path, &std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(updated.contains("cli = { path = \"tools/cli\", version = \"0.5.1\" }\n"));
+        assert!(updated.contains("\n[workspace.package]\nversion = \"0.5.1\"\n"));
+        assert!(updated.contains("core = { path = \"crates/core\", version = \"1\" } # shared\n"));
+        assert!(plan.apply_to_toml(&path, "[package]\nname = \"app\"").is_err());
+    }
+
+    assert!(ReleasePlan::version_bump(root, &members, "ignored", &VersionBump::Patch).is_err());
+}
+
+#[test]
+fn hoist_dependencies() {
+    use cargo_toml::{Dependency, DependencyHoisting, Kind};
+
+    let root = Path::new("tests/workspace_graph");
+    let mut ws = Manifest::from_path(root.join("Cargo.toml")).unwrap();
+    let mut members = ws.load_workspace_members(root).unwrap();
+
+    let h = DependencyHoisting::analyze(&ws, root, &members);
+    assert_eq!(h.conflicts.len(), 1);
+    assert_eq!(h.conflicts[0].crate_name, "log");
+    assert_eq!(h.conflicts[0].requirements, [("net".into(), "0.3".into()), ("testkit".into(), "0.4".into())]);
+
+    let keys: Vec<_> = h.hoist.iter().map(|h| h.key.as_str()).collect();
+    assert_eq!(keys, ["serde", "testkit"]);
+    let serde = &h.hoist[0];
+    assert_eq!(serde.template, Dependency::Simple("1.0.200".into()));
+    let cli = serde.members.iter().find(|e| e.member == "cli").unwrap();
+    assert_eq!(cli.inherited.features, ["derive", "rc"]);
+    assert!(cli.inherited.optional);
+    let testkit = &h.hoist[1];
+    assert_eq!(testkit.template.detail().unwrap().path.as_deref(), Some("crates/testkit"));
+    assert_eq!(testkit.members.iter().map(|e| (e.member.as_str(), e.kind)).collect::<Vec<_>>(), [("core", Kind::Dev), ("net", Kind::Build)]);
+
+    h.apply(ws.workspace.as_mut().unwrap(), &mut members);
+    assert_eq!(ws.workspace.as_ref().unwrap().dependencies.len(), 3);
+    let core = members.iter().find(|m| m.name() == "core").unwrap();
+    assert!(matches!(&core.manifest.dev_dependencies["testkit"], Dependency::Inherited(d) if d.workspace && d.features.is_empty()));
+    assert!(matches!(&core.manifest.dependencies["serde"], Dependency::Inherited(d) if d.features == ["derive"]));
+}
+
+#[test]
+fn lint_workspace() {
+    use cargo_toml::WorkspaceLint;
+
+    let root = Path::new("tests/workspace_lint");
+    let ws = Manifest::from_path(root.join("Cargo.toml")).unwrap();
+    let lints = ws.lint_workspace(root).unwrap();
+    assert_eq!(lints, [
+        WorkspaceLint::DuplicatesWorkspaceValue { member: "a".into(), field: "license".into() },
+        WorkspaceLint::DuplicatesWorkspaceValue { member: "a".into(), field: "readme".into() },
+        WorkspaceLint::MissingFromWorkspace { member: "a".into(), field: "rust-version".into() },
+        WorkspaceLint::MissingFromWorkspace { member: "b".into(), field: "publish".into() },
+        WorkspaceLint::MissingWorkspaceDependency { member: "a".into(), dependency: "nope".into() },
+        WorkspaceLint::OptionalWorkspaceDependency { dependency: "opt".into() },
+        WorkspaceLint::UnusedWorkspaceDependency { dependency: "unused".into() },
+    ]);
+    assert_eq!(lints.iter().filter(|l| l.is_error()).count(), 4);
+    assert_eq!(lints[2].to_string(), "`a` inherits `rust-version`, but it's not in [workspace.package]");
+
+    assert!(Manifest::from_path("tests/inheritance/Cargo.toml").unwrap().lint_workspace(Path::new("tests/inheritance")).unwrap().is_empty());
+    assert!(Manifest::from_path("tests/workspace_lint_defaults/Cargo.toml").unwrap().lint_workspace(Path::new("tests/workspace_lint_defaults")).unwrap().is_empty());
+}
+
+#[test]
+fn typed_profile_settings() {
+    let m = Manifest::from_str(r#"
+        [package]
+        name = "p"
+        version = "1.0.0"
+        [profile.release]
+        opt-level = "z"
+        panic = "abort"
+        split-debuginfo = "packed"
+        [profile.dev]
+        opt-level = "1"
+    "#).unwrap();
+    let release = m.profile.release.as_ref().unwrap();
+    assert_eq!(Some(OptLevel::Z), release.opt_level);
+    assert_eq!(Some(cargo_toml::PanicStrategy::Abort), release.panic);
+    assert_eq!(Some(cargo_toml::SplitDebuginfo::Packed), release.split_debuginfo);
+    assert_eq!(Some(OptLevel::O1), m.profile.dev.as_ref().unwrap().opt_level);
+
+    let out = toml::to_string(&m).unwrap();
+    assert!(out.contains("opt-level = 1\n"), "{out}");
+    assert!(out.contains("opt-level = \"z\""), "{out}");
+    assert!(out.contains("panic = \"abort\""), "{out}");
+
+    let bad = |profile: &str| Manifest::from_str(&format!("[package]\nname = \"p\"\nversion = \"1.0.0\"\n[profile.release]\n{profile}\n")).unwrap_err().to_string();
+    assert!(bad("opt-level = 4").contains("opt-level must be"));
+    assert!(bad("panic = \"crash\"").contains("panic must be"));
+    assert!(bad("split-debuginfo = true").contains("split-debuginfo"));
+}
+
+#[test]
+fn profile_overrides() {
+    let m = Manifest::from_path("tests/autobin/Cargo.toml").unwrap();
+    let dev = m.profile.dev.as_ref().unwrap();
+    let build = dev.build_override.as_ref().unwrap();
+    assert_eq!(Some(OptLevel::O0), build.opt_level);
+    assert_eq!(Some(256), build.codegen_units);
+    let test = m.profile.test.as_ref().unwrap();
+    assert_eq!(Some(cargo_toml::DebugSetting::None), test.package["*"].debug);
+
+    let m = Manifest::from_str(r#"
+        [package]
+        name = "p"
+        version = "1.0.0"
+        [profile.release.package."*"]
+        opt-level = 2
+        [profile.release.package.foo]
+        opt-level = "s"
+        [profile.release.package."foo@1.2"]
+        opt-level = "z"
+    "#).unwrap();
+    let release = m.profile.release.as_ref().unwrap();
+    let level = |name, version| release.package_override(name, version).and_then(|o| o.opt_level);
+    assert_eq!(Some(OptLevel::Z), level("foo", Some("1.2.3")));
+    assert_eq!(Some(OptLevel::S), level("foo", Some("1.20.0")));
+    assert_eq!(Some(OptLevel::S), level("foo", None));
+    assert_eq!(Some(OptLevel::O2), level("bar", Some("1.2.3")));
+    assert!(toml::to_string(&m).unwrap().contains("[profile.release.package.\"foo@1.2\"]"));
+
+    let bad = |profile: &str| Manifest::from_str(&format!("[package]\nname = \"p\"\nversion = \"1.0.0\"\n{profile}\n")).unwrap_err().to_string();
+    assert!(bad("[profile.dev.package.foo]\npanic = \"abort\"").contains("`panic` can't be overridden"));
+    assert!(bad("[profile.dev.build-override]\nlto = true").contains("`lto` can't be overridden"));
+    assert!(bad("[profile.dev.package.foo.package.bar]\ndebug = 1").contains("can't be nested"));
+    assert!(bad("[profile.dev.package.foo]\nopt-level = 9").contains("opt-level must be"));
+}
+
+struct FakeFs(&'static [&'static str]);
+
+impl cargo_toml::AbstractFilesystem for FakeFs {
+    fn file_names_in(&self, dir: &str) -> std::io::Result<std::collections::HashSet<Box<str>>> {
+        let prefix = if dir.is_empty() { String::new() } else { format!("{dir}/") };
+        let names: std::collections::HashSet<_> = self.0.iter().filter_map(|p| p.strip_prefix(&prefix)).map(|p| p.split('/').next().unwrap().into()).collect();
+        if names.is_empty() { Err(std::io::ErrorKind::NotFound.into()) } else { Ok(names) }
+    }
+}
+
+#[test]
+fn check_products() {
+    use cargo_toml::{ProductKind, ProductProblem};
+
+    let fs = FakeFs(&["Cargo.toml", "src/lib.rs", "src/main.rs", "src/bin/tool.rs", "examples/demo.rs"]);
+    let mut m = Manifest::from_str(r#"
+        [package]
+        name = "my-pkg"
+        version = "1.0.0"
+        edition = "2021"
+        default-run = "my-pkg"
+    "#).unwrap();
+    m.complete_from_abstract_filesystem::<cargo_toml::Value, _>(&fs, None).unwrap();
+    assert_eq!(Vec::<ProductProblem>::new(), m.check_products(&fs).unwrap());
+
+    let mut m = Manifest::from_str(r#"
+        [package]
+        name = "pkg"
+        version = "1.0.0"
+        edition = "2021"
+        default-run = "nope"
+        [lib]
+        name = "bad-name"
+        proc-macro = true
+        crate-type = ["proc-macro", "rlib"]
+        [[bin]]
+        name = "bad-name"
+        path = "src/main.rs"
+        [[bin]]
+        name = "other"
+        path = "src/other.rs"
+        [[example]]
+        name = "demo"
+        path = "examples/demo.rs"
+        [[example]]
+        name = "demo"
+        path = "examples/demo2.rs"
+    "#).unwrap();
+    m.complete_from_abstract_filesystem::<cargo_toml::Value, _>(&fs, None).unwrap();
+    let problems = m.check_products(&fs).unwrap();
+    assert_eq!(problems, [
+        ProductProblem::DuplicateName { kind: ProductKind::Example, name: "demo".into() },
+        ProductProblem::BinNamedLikeLib { name: "bad-name".into() },
+        ProductProblem::InvalidLibName { name: "bad-name".into() },
+        ProductProblem::MissingFile { kind: ProductKind::Bin, name: Some("other".into()), path: "src/other.rs".into() },
+        ProductProblem::MissingFile { kind: ProductKind::Example, name: Some("demo".into()), path: "examples/demo2.rs".into() },
+        ProductProblem::ProcMacroWithCrateType { crate_type: vec!["proc-macro".into(), "rlib".into()] },
+        ProductProblem::UnknownDefaultRun { name: "nope".into() },
+    ]);
+    assert_eq!(problems[2].to_string(), "library name `bad-name` is not a valid Rust identifier");
+}
+
+#[test]
+fn strict_unused_keys() {
+    let (m, unused) = Manifest::from_str_strict(r#"
+        [package]
+        name = "p"
+        version = "1.0.0"
+        nmae = "typo"
+        [package.metadata.anything]
+        goes = true
+        [dependancies]
+        serde = "1"
+        [dependencies]
+        log = { version = "0.4", defualt-features = false, artifact = "bin" }
+        [profile.release]
+        opt_level = 3
+        [[bin]]
+        name = "x"
+        pth = "src/x.rs"
+        [workspace.metadata]
+        whatever = 1
+        [badges]
+        maintenance = { status = "actively-developed" }
+        shiny = { repository = "x" }
+        [lints.rust]
+        unsafe_code = { level = "deny", priorty = 1 }
+        [patch.crates-io]
+        log = { path = "../log", brnach = "main" }
+        [replace]
+        "foo:1.0.0" = { git = "https://example.com/foo", revv = "abc" }
+    "#).unwrap();
+    assert_eq!("p", m.package().name);
+    let unused: Vec<_> = unused.iter().map(|u| u.to_string()).collect();
+    assert_eq!(unused, [
+        "unused manifest key: badges.shiny",
+        "unused manifest key: bin.0.pth (did you mean `path`?)",
+        "unused manifest key: dependancies (did you mean `dependencies`?)",
+        "unused manifest key: dependencies.log.defualt-features (did you mean `default-features`?)",
+        "unused manifest key: lints.rust.unsafe_code.priorty (did you mean `priority`?)",
+        "unused manifest key: package.nmae (did you mean `name`?)",
+        "unused manifest key: patch.crates-io.log.brnach (did you mean `branch`?)",
+        "unused manifest key: profile.release.opt_level (did you mean `opt-level`?)",
+        "unused manifest key: replace.foo:1.0.0.revv (did you mean `rev`?)",
+    ]);
+
+    let (_, unused) = Manifest::from_str_strict(&std::fs::read_to_string("Cargo.toml").unwrap()).unwrap();
+    assert_eq!(unused, []);
+}
+
+#[test]
+fn lossy_parse() {
+    let src = r#"
+        [package]
+        name = "p"
+        version = "1.0.0"
+        edition = "2099"
+        description = "still here"
+        [dependencies]
+        good = "1"
+        bad = { version = "1", optional = "maybe" }
+        [profile.release]
+        opt-level = 9
+        debug = true
+        [[bin]]
+        name = "ok"
+        [[bin]]
+        name = 5
+        [lib]
+        path = "src/lib.rs"
+    "#;
+    assert!(Manifest::from_str(src).is_err());
+    let (m, errors) = Manifest::from_str_lossy(src).unwrap();
+    let paths: Vec<_> = errors.iter().map(|e| e.path.join(".")).collect();
+    assert_eq!(paths, ["bin.1.name", "dependencies.bad.optional", "package.edition", "profile.release.opt-level"]);
+    let package = m.package();
+    assert_eq!("p", package.name);
+    assert_eq!(Edition::E2015, package.edition());
+    assert_eq!(Some("still here"), package.description());
+    assert!(m.dependencies.contains_key("good"));
+    assert!(!m.dependencies["bad"].optional());
+    let release = m.profile.release.as_ref().unwrap();
+    assert_eq!(None, release.opt_level);
+    assert!(release.debug.is_some());
+    assert_eq!(2, m.bin.len());
+    assert_eq!(None, m.bin[1].name);
+    assert_eq!(Some("src/lib.rs"), m.lib.as_ref().unwrap().path.as_deref());
+
+    let (_, errors) = Manifest::from_str_lossy("[package]\nname = 1\n").unwrap();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[1].to_string().starts_with("package: "), "{}", errors[1]);
+    // `edition` is checked with the name filled in, and isn't blamed for the invalid name
+    let (_, errors) = Manifest::from_str_lossy("[package]\nname = 1\nedition = \"2021\"\n[lib]\nedition = \"1999\"\n").unwrap();
+    let paths: Vec<_> = errors.iter().map(|e| e.path.join(".")).collect();
+    assert_eq!(paths, ["lib.edition", "package.name", "package"]);
+    assert!(Manifest::from_str_lossy("[package").is_err());
+}
+
+#[test]
+fn unknown_keys_roundtrip() {
+    let src = r#"
+        cargo-features = ["different-binary-name"]
+
+        [package]
+        name = "p"
+        version = "1.0.0"
+        future-key = { nested = [1, 2] }
+
+        [workspace]
+        new-setting = "x"
+
+        [profile.release]
+        trim-paths = true
+
+        [profile.release.package.foo]
+        debug = 1
+        later = 2
+
+        [[bin]]
+        name = "p"
+        filename = "p-cli"
+
+        [target.'cfg(unix)'.dependencies]
+        libc = "0.2"
+
+        [target.'cfg(unix)']
+        runner = "x"
+    "#;
+    let m = Manifest::from_str(src).unwrap();
+    assert!(m.extra.contains_key("cargo-features"));
+    assert!(m.package().extra.contains_key("future-key"));
+    assert!(m.workspace.as_ref().unwrap().extra.contains_key("new-setting"));
+    let release = m.profile.release.as_ref().unwrap();
+    assert_eq!(Some(&toml::Value::Boolean(true)), release.extra.get("trim-paths"));
+    assert!(release.package["foo"].extra.contains_key("later"));
+    assert!(m.bin[0].extra.contains_key("filename"));
+    assert!(m.target["cfg(unix)"].extra.contains_key("runner"));
+
+    let out = toml::to_string(&m).unwrap();
+    assert_eq!(m, Manifest::from_str(&out).unwrap());
+    for key in ["cargo-features", "future-key", "new-setting", "trim-paths", "later", "filename", "runner"] {
+        assert!(out.contains(key), "{key} missing in {out}");
+    }
+
+    let (_, unused) = Manifest::from_str_strict("[workspace.package]\nversion = \"1.0.0\"\nedtion = \"2021\"\n[profile.dev.build-override]\nopt_level = 1\n").unwrap();
+    let unused: Vec<_> = unused.iter().map(|u| u.to_string()).collect();
+    assert_eq!(unused, [
+        "unused manifest key: profile.dev.build-override.opt_level (did you mean `opt-level`?)",
+        "unused manifest key: workspace.package.edtion (did you mean `edition`?)",
+    ]);
+}
+
+#[test]
+fn parse_error_location() {
+    let err = Manifest::from_str("[package]\nname = \"p\"\nversion = \"1.0.0\"\nedition = \"2099\"\n").unwrap_err().to_string();
+    assert!(err.contains("line 4, column 11"), "{err}");
+}
+
+#[test]
+fn explicit_keys() {
+    let src = r#"[package]
+name = "p"
+version = "0.0.0"
+autobins = true
+
+[[bin]]
+name = "p"
+test = true
+required-features = []
+"#;
+    let m = Manifest::from_str(src).unwrap();
+    let package = m.package();
+    assert!(package.explicit.contains("version"));
+    assert!(package.explicit.contains("autobins"));
+    assert!(!package.explicit.contains("edition"));
+    assert!(!package.explicit.contains("autotests"));
+    assert!(m.bin[0].explicit.contains("test"));
+    assert!(!m.bin[0].explicit.contains("harness"));
+    assert!(m.explicit.contains("bin"));
+    // explicit 0.0.0 doesn't disable publishing
+    assert_eq!(true, *package.publish());
+    assert_eq!(src, m.to_string_as_written().unwrap());
+
+    let m = Manifest::from_str("[package]\nname = \"p\"\n\n[profile.release]\ndebug = 1\n").unwrap();
+    assert_eq!(false, *m.package().publish());
+    assert!(!m.package().explicit.contains("version"));
+    assert!(m.profile.release.as_ref().unwrap().explicit.contains("debug"));
+    assert!(toml::to_string(&m).unwrap().contains("edition"));
+    assert_eq!("[package]\nname = \"p\"\n\n[profile.release]\ndebug = 1\n", m.to_string_as_written().unwrap());
+
+    // a version set after parsing keeps the defaulted `publish = false`
+    let mut m = Manifest::from_str("[package]\nname = \"p\"\n").unwrap();
+    m.package.as_mut().unwrap().version = cargo_toml::Inheritable::Set("1.0.0".into());
+    assert_eq!("[package]\nname = \"p\"\nversion = \"1.0.0\"\npublish = false\n", m.to_string_as_written().unwrap());
+
+    // without `explicit`, only the defaults are removed
+    let m: Manifest = toml::from_str("[package]\nname = \"p\"\nversion = \"1.0.0\"\n").unwrap();
+    assert!(m.package().explicit.is_empty());
+    assert_eq!("[package]\nname = \"p\"\nversion = \"1.0.0\"\n", m.to_string_as_written().unwrap());
+}
+
+#[test]
+fn deprecated_keys() {
+    let src = r#"[project]
+name = "old"
+authors = ["Someone"]
+
+[lib]
+crate_type = ["cdylib"] # C API
+
+[dev_dependencies]
+serde = { version = "1", default_features = false }
+
+[target.'cfg(unix)'.build_dependencies]
+cc = "1"
+
+[badges]
+travis-ci = { repository = "a/b" }
+maintenance = { status = "passively-maintained" }
+
+[replace]
+"foo:0.1.0" = { path = "foo" }
+"#;
+    let found: Vec<_> = Deprecation::find(src).unwrap().iter().map(|d| d.to_string()).collect();
+    assert_eq!(found, [
+        "`dev_dependencies` is deprecated; use `dev-dependencies` instead",
+        "`dev_dependencies.serde.default_features` is deprecated; use `default-features` instead",
+        "`lib.crate_type` is deprecated; use `crate-type` instead",
+        "`project` is deprecated; use `package` instead",
+        "`target.cfg(unix).build_dependencies` is deprecated; use `build-dependencies` instead",
+        "[replace] is deprecated; use [patch] instead",
+        "badge `travis-ci` is not displayed on crates.io",
+        "`package.authors` is deprecated",
+    ]);
+    assert!(Deprecation::find("[package]\nname = \"new\"\n[dependencies]\nproc_macro = \"1\"\n").unwrap().is_empty());
+}
+
+#[test]
+#[cfg(feature = "edit")]
+fn migrate_deprecated_keys() {
+    let src = "[project]\nname = \"old\" # comment\n\n[lib]\ncrate_type = [\"cdylib\"] # C API\npath = \"lib.rs\"\n\n[dev_dependencies]\nserde = { version = \"1\", default_features = false, features = [] }\n\n[[bin]]\nname = \"b\"\nproc_macro = false\n\n[badges]\ntravis-ci = { repository = \"a/b\" }\n\n[replace]\n\"foo:0.1.0\" = { path = \"foo\" }\n";
+    let (migrated, remaining) = Deprecation::migrate(src).unwrap();
+    assert_eq!(migrated, "[package]\nname = \"old\" # comment\n\n[lib]\ncrate-type = [\"cdylib\"] # C API\npath = \"lib.rs\"\n\n[dev-dependencies]\nserde = { version = \"1\", default-features = false, features = [] }\n\n[[bin]]\nname = \"b\"\nproc-macro = false\n\n[replace]\n\"foo:0.1.0\" = { path = \"foo\" }\n");
+    assert_eq!(remaining, [Deprecation::Replace]);
+    assert_eq!(Deprecation::find(&migrated).unwrap(), [Deprecation::Replace]);
+}
+
+#[test]
+fn edition_migration() {
+    use cargo_toml::{EditionChange, ProductKind, Resolver};
+
+    let src = r#"[package]
+name = "old"
+edition = "2015"
+rust-version = "1.40" # MSRV
+
+[[bin]]
+name = "main"
+path = "src/main.rs"
+
+[[example]]
+name = "demo"
+edition = "2018"
+"#;
+    let fs = FakeFs(&["Cargo.toml", "src/main.rs", "src/bin/tool.rs", "src/bin/multi/main.rs", "examples/demo.rs"]);
+    let m = Manifest::from_str(src).unwrap();
+    let migration = m.edition_migration(Edition::E2021, &fs).unwrap();
+    assert_eq!(migration.from, Edition::E2015);
+    assert_eq!(migration.changes, [
+        EditionChange::RustVersion { old: "1.40".into(), new: "1.56".into() },
+        EditionChange::ImpliedResolver { old: Resolver::V1, new: Resolver::V2 },
+        EditionChange::ProductEdition { kind: ProductKind::Example, name: Some("demo".into()), edition: Edition::E2018 },
+        EditionChange::Discovered { kind: ProductKind::Bin, path: "src/bin/multi/main.rs".into() },
+        EditionChange::Discovered { kind: ProductKind::Bin, path: "src/bin/tool.rs".into() },
+    ]);
+
+    let mut applied = m.clone();
+    migration.apply(&mut applied);
+    assert_eq!(Edition::E2021, applied.package().edition());
+    assert_eq!(Some("1.56"), applied.package().rust_version());
+    assert!(!applied.package().autobins);
+    // pinned editions of products are left alone
+    assert_eq!(Some(Edition::E2018), applied.example[0].edition);
+    assert_eq!(applied.edition_migration(Edition::E2021, &fs).unwrap().changes, [
+        EditionChange::ProductEdition { kind: ProductKind::Example, name: Some("demo".into()), edition: Edition::E2018 },
+    ]);
+
+    // explicit `autobins` keeps discovery on in 2015
+    let m = Manifest::from_str("[package]\nname = \"x\"\nautobins = true\n[[bin]]\nname = \"x\"\n").unwrap();
+    assert!(m.edition_migration(Edition::E2018, &fs).unwrap().changes.is_empty());
+
+    let ws = Manifest::from_str("[workspace]\nmembers = [\"a\"]\n").unwrap();
+    assert_eq!(ws.edition_migration(Edition::E2024, &fs).unwrap().changes, [EditionChange::WorkspaceResolver { resolver: Resolver::V3 }]);
+
+    #[cfg(feature = "edit")]
+    assert_eq!(migration.apply_to_toml(src).unwrap(), r#"[package]
+name = "old"
+edition = "2021"
+rust-version = "1.56" # MSRV
+autobins = false
+
+[[bin]]
+name = "main"
+path = "src/main.rs"
+
+[[example]]
+name = "demo"
+edition = "2018"
+"#);
+}
+
+#[test]
+fn crate_types() {
+    let m = Manifest::from_str("[package]\nname = \"c\"\n[lib]\ncrate-type = [\"cdylib\", \"rlib\", \"future-type\"]\n").unwrap();
+    let lib = m.lib.as_ref().unwrap();
+    assert_eq!(lib.crate_type, [CrateType::Cdylib, CrateType::Rlib, CrateType::Other("future-type".into())]);
+    assert!(lib.crate_type[0].is_c_linkable());
+    assert!(!lib.crate_type[1].is_c_linkable());
+    assert!(lib.crate_type[1].is_rust_linkable());
+    assert!(lib.crate_type[1].conflicts_with_proc_macro());
+    assert!(!CrateType::ProcMacro.conflicts_with_proc_macro());
+    assert!(toml::to_string(&m).unwrap().contains(r#"crate-type = ["cdylib", "rlib", "future-type"]"#));
+}
+
+#[test]
+fn resolved_products() {
+    use cargo_toml::ProductKind;
+
+    let fs = FakeFs(&["Cargo.toml", "build.rs", "src/lib.rs", "src/main.rs", "src/bin/my-tool.rs", "tests/it.rs"]);
+    let mut m = Manifest::from_str(r#"
+        [package]
+        name = "my-pkg"
+        edition = "2021"
+        [lib]
+        crate-type = ["cdylib", "rlib"]
+        [[bin]]
+        name = "my-tool"
+        required-features = ["cli"]
+        [[test]]
+        name = "it"
+        edition = "2018"
+    "#).unwrap();
+    m.complete_from_abstract_filesystem::<cargo_toml::Value, _>(&fs, None).unwrap();
+    let products = m.products().unwrap();
+    let summary: Vec<_> = products.iter().map(|p| (p.kind, p.name.as_str(), p.crate_name.as_str(), p.path.to_str().unwrap(), p.edition)).collect();
+    assert_eq!(summary, [
+        (ProductKind::Lib, "my_pkg", "my_pkg", "src/lib.rs", Edition::E2021),
+        (ProductKind::Bin, "my-pkg", "my_pkg", "src/main.rs", Edition::E2021),
+        (ProductKind::Bin, "my-tool", "my_tool", "src/bin/my-tool.rs", Edition::E2021),
+        (ProductKind::Test, "it", "it", "tests/it.rs", Edition::E2018),
+        (ProductKind::BuildScript, "build-script-build", "build_script_build", "build.rs", Edition::E2021),
+    ]);
+    assert_eq!(products[0].file_names("linux"), ["libmy_pkg.so", "libmy_pkg.rlib"]);
+    assert_eq!(products[0].file_names("windows"), ["my_pkg.dll", "libmy_pkg.rlib"]);
+    assert_eq!(products[2].file_names("windows"), ["my-tool.exe"]);
+    assert_eq!(products[2].required_features, ["cli"]);
+    assert_eq!(products[3].crate_type, [CrateType::Bin]);
+    assert!(products[3].file_names("macos").is_empty());
+
+    let fs = FakeFs(&["Cargo.toml", "src/lib.rs"]);
+    let mut m = Manifest::from_str("[package]\nname = \"pm\"\nedition = \"2021\"\n[lib]\nproc-macro = true\n").unwrap();
+    m.complete_from_abstract_filesystem::<cargo_toml::Value, _>(&fs, None).unwrap();
+    let products = m.products().unwrap();
+    assert_eq!(products[0].crate_type, [CrateType::ProcMacro]);
+    assert_eq!(products[0].file_names("linux"), ["libpm.so"]);
+}
+
+#[test]
+fn autodiscovery_report() {
+    use cargo_toml::{DiscoveryOutcome, IgnoredReason, ProductKind};
+
+    let fs = FakeFs(&["Cargo.toml", "src/main.rs", "src/bin/tool.rs", "src/bin/extra.rs", "src/bin/server/main.rs", "examples/demo.rs", "tests/it.rs"]);
+    let src = r#"
+        [package]
+        name = "pkg"
+        autoexamples = false
+        [[bin]]
+        name = "server"
+        [[bin]]
+        name = "tool"
+        path = "src/tool_main.rs"
+    "#;
+    let m = Manifest::from_str(src).unwrap();
+    let report: Vec<_> = m.autodiscovery_report(&fs).unwrap().into_iter().map(|c| (c.kind, c.path, c.outcome)).collect();
+    assert_eq!(report, [
+        (ProductKind::Bin, "src/bin/extra.rs".into(), DiscoveryOutcome::Ignored(IgnoredReason::Edition2015)),
+        (ProductKind::Bin, "src/bin/server/main.rs".into(), DiscoveryOutcome::Merged),
+        (ProductKind::Bin, "src/bin/tool.rs".into(), DiscoveryOutcome::Ignored(IgnoredReason::NameTaken)),
+        (ProductKind::Bin, "src/main.rs".into(), DiscoveryOutcome::Ignored(IgnoredReason::Edition2015)),
+        (ProductKind::Example, "examples/demo.rs".into(), DiscoveryOutcome::Ignored(IgnoredReason::Disabled)),
+        (ProductKind::Test, "tests/it.rs".into(), DiscoveryOutcome::Inferred),
+    ]);
+
+    let mut m = Manifest::from_str(src).unwrap();
+    m.complete_from_abstract_filesystem::<cargo_toml::Value, _>(&fs, None).unwrap();
+    let bins: Vec<_> = m.bin.iter().map(|b| b.path.as_deref().unwrap()).collect();
+    assert_eq!(bins, ["src/bin/server/main.rs", "src/tool_main.rs"]);
+    assert!(m.example.is_empty());
+
+    let mut m = Manifest::from_str(&src.replace("\"pkg\"", "\"pkg\"\nedition = \"2018\"")).unwrap();
+    m.complete_from_abstract_filesystem::<cargo_toml::Value, _>(&fs, None).unwrap();
+    let bins: Vec<_> = m.bin.iter().map(|b| b.name.as_deref().unwrap()).collect();
+    assert_eq!(bins, ["extra", "pkg", "server", "tool"]);
+
+    // without `explicit`, `autobins = true` is assumed to be written
+    let m: Manifest = toml::from_str("[package]\nname = \"pkg\"\n[[bin]]\nname = \"server\"\n").unwrap();
+    assert!(m.autodiscovery_report(&fs).unwrap().iter().any(|c| c.path == "src/bin/extra.rs" && c.outcome == DiscoveryOutcome::Inferred));
+
+    let fs = FakeFs(&["Cargo.toml", "src/bin/dup.rs", "src/bin/dup/main.rs", "src/bin/one.rs"]);
+    let mut m = Manifest::from_str("[package]\nname = \"pkg\"\nedition = \"2021\"\n").unwrap();
+    let report = m.autodiscovery_report(&fs).unwrap();
+    let outcomes: Vec<_> = report.iter().map(|c| (c.path.as_str(), c.outcome)).collect();
+    assert_eq!(outcomes, [
+        ("src/bin/dup.rs", DiscoveryOutcome::Ignored(IgnoredReason::Ambiguous)),
+        ("src/bin/dup/main.rs", DiscoveryOutcome::Ignored(IgnoredReason::Ambiguous)),
+        ("src/bin/one.rs", DiscoveryOutcome::Inferred),
+    ]);
+    assert_eq!(report[0].to_string(), "`src/bin/dup.rs` is ignored, because another file would also be bin `dup`");
+    m.complete_from_abstract_filesystem::<cargo_toml::Value, _>(&fs, None).unwrap();
+    assert_eq!(m.bin.len(), 1);
+}